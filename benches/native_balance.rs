@@ -0,0 +1,213 @@
+//! Compares `NativeBalance` operations with the previous linear-scan implementation.
+//! Besides the time, every operation reports its heap allocations and allocated bytes,
+//! as a gas-like count that does not depend on the machine. The clones setting up each
+//! operation are counted on both sides.
+//!
+//! Run with `cargo bench --bench native_balance`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use cosmwasm_std::{coin, Coin};
+use cw_utils::NativeBalance;
+
+const ITERATIONS: u32 = 200;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Counts every allocation (and reallocation) made through the system allocator
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Average cost of one operation
+struct Cost {
+    time: Duration,
+    allocations: u64,
+    bytes: u64,
+}
+
+/// The linear-scan implementation NativeBalance used before, kept as a baseline
+#[derive(Clone)]
+struct LinearBalance(Vec<Coin>);
+
+impl LinearBalance {
+    fn find(&self, denom: &str) -> Option<(usize, &Coin)> {
+        self.0.iter().enumerate().find(|(_i, c)| c.denom == denom)
+    }
+
+    fn has(&self, required: &Coin) -> bool {
+        self.find(&required.denom)
+            .map(|(_, c)| c.amount >= required.amount)
+            .unwrap_or(false)
+    }
+
+    fn add_coin(&mut self, other: Coin) {
+        match self.find(&other.denom) {
+            Some((i, c)) => self.0[i].amount = c.amount + other.amount,
+            None => match self.0.iter().position(|c| c.denom >= other.denom) {
+                Some(pos) => self.0.insert(pos, other),
+                None => self.0.push(other),
+            },
+        }
+    }
+
+    fn add_balance(&mut self, other: LinearBalance) {
+        for coin in other.0 {
+            self.add_coin(coin);
+        }
+    }
+
+    fn sub_coin(mut self, other: Coin) -> Option<Self> {
+        let (i, c) = self.find(&other.denom)?;
+        let remainder = c.amount.checked_sub(other.amount).ok()?;
+        if remainder.is_zero() {
+            self.0.remove(i);
+        } else {
+            self.0[i].amount = remainder;
+        }
+        Some(self)
+    }
+
+    fn sub_many(self, amount: Vec<Coin>) -> Option<Self> {
+        let mut res = self;
+        for coin in amount {
+            res = res.sub_coin(coin.clone())?;
+        }
+        Some(res)
+    }
+}
+
+/// sorted IBC-like denoms, `step` apart so two sets can interleave
+fn coins(count: usize, offset: usize, step: usize) -> Vec<Coin> {
+    (0..count)
+        .map(|i| coin(1000, format!("ibc/{:064X}", offset + i * step)))
+        .collect()
+}
+
+fn measure(mut f: impl FnMut()) -> Cost {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let time = start.elapsed() / ITERATIONS;
+    Cost {
+        time,
+        allocations: (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / ITERATIONS as u64,
+        bytes: (ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes) / ITERATIONS as u64,
+    }
+}
+
+fn report(name: &str, size: usize, baseline: Cost, current: Cost) {
+    println!(
+        "{:<12} {:>5} denoms | linear {:>11.2?} {:>6} allocs {:>9} B | sorted {:>11.2?} {:>6} allocs {:>9} B | speedup {:>6.1}x",
+        name,
+        size,
+        baseline.time,
+        baseline.allocations,
+        baseline.bytes,
+        current.time,
+        current.allocations,
+        current.bytes,
+        baseline.time.as_secs_f64() / current.time.as_secs_f64().max(f64::EPSILON)
+    );
+}
+
+fn main() {
+    for size in [10, 100, 500, 2000] {
+        let left = coins(size, 0, 2);
+        let right = coins(size, 1, 2);
+        let linear = LinearBalance(left.clone());
+        let sorted = NativeBalance(left.clone());
+
+        // hits and misses are both binary searches on the sorted balance
+        for (name, lookups) in [("has (hit)", &left), ("has (miss)", &right)] {
+            let baseline = measure(|| {
+                for c in lookups.iter() {
+                    black_box(linear.has(c));
+                }
+            });
+            let current = measure(|| {
+                for c in lookups.iter() {
+                    black_box(sorted.has(c));
+                }
+            });
+            report(name, size, baseline, current);
+        }
+
+        let baseline = measure(|| {
+            let mut balance = linear.clone();
+            balance.add_balance(LinearBalance(right.clone()));
+            black_box(balance);
+        });
+        let current = measure(|| {
+            let mut balance = sorted.clone();
+            balance += NativeBalance(right.clone());
+            black_box(balance);
+        });
+        report("add balance", size, baseline, current);
+
+        let baseline = measure(|| {
+            let mut balance = linear.clone();
+            for c in right.iter().cloned() {
+                balance.add_coin(c);
+            }
+            black_box(balance);
+        });
+        let current = measure(|| {
+            let mut balance = sorted.clone();
+            for c in right.iter().cloned() {
+                balance += c;
+            }
+            black_box(balance);
+        });
+        report("insert coins", size, baseline, current);
+
+        let baseline = measure(|| {
+            black_box(linear.clone().sub_many(left.clone()).unwrap());
+        });
+        let current = measure(|| {
+            black_box((sorted.clone() - left.clone()).unwrap());
+        });
+        report("sub many", size, baseline, current);
+
+        let baseline = measure(|| {
+            let mut balance = LinearBalance(vec![]);
+            for c in right.iter().rev().chain(left.iter()).cloned() {
+                balance.add_coin(c);
+            }
+            black_box(balance);
+        });
+        let current = measure(|| {
+            let mut balance =
+                NativeBalance(right.iter().rev().chain(left.iter()).cloned().collect());
+            balance.normalize();
+            black_box(balance);
+        });
+        report("normalize", size, baseline, current);
+    }
+}
@@ -0,0 +1,191 @@
+//! Compares reply parsing with the previous implementation, which copied the remaining
+//! buffer for every field it read.
+//!
+//! Run with `cargo bench --bench parse_reply`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use cosmwasm_std::{Binary, Reply, SubMsgResponse, SubMsgResult};
+use cw_utils::{
+    parse_execute_response_data, parse_instantiate_response_data, parse_reply_execute_data,
+    parse_reply_instantiate_data,
+};
+
+const ITERATIONS: u32 = 200;
+
+/// The Vec based parser used before, kept as a baseline
+mod copying {
+    use cosmwasm_std::Binary;
+
+    fn parse_varint(data: &mut Vec<u8>) -> Option<usize> {
+        let mut len: u64 = 0;
+        for i in 0..9 {
+            let byte = *data.get(i)?;
+            len += ((byte & 0x7f) as u64) << (i * 7);
+            if byte & 0x80 == 0 {
+                *data = data[i + 1..].to_owned();
+                return Some(len as usize);
+            }
+        }
+        None
+    }
+
+    fn parse_length_prefixed(data: &mut Vec<u8>, field_number: u8) -> Option<Vec<u8>> {
+        if data.is_empty() {
+            return Some(vec![]);
+        };
+        let mut rest_1 = data.split_off(1);
+        if data[0] >> 3 != field_number || data[0] & 0b11 != 2 {
+            return None;
+        }
+        let len = parse_varint(&mut rest_1)?;
+        if rest_1.len() < len {
+            return None;
+        }
+        *data = rest_1.split_off(len);
+        Some(rest_1)
+    }
+
+    fn parse_bytes(data: &mut Vec<u8>, field_number: u8) -> Option<Option<Binary>> {
+        let bytes = parse_length_prefixed(data, field_number)?;
+        Some(if bytes.is_empty() {
+            None
+        } else {
+            Some(Binary(bytes))
+        })
+    }
+
+    pub fn parse_instantiate(data: &[u8]) -> Option<(String, Option<Binary>)> {
+        let mut data = data.to_vec();
+        let address = String::from_utf8(parse_length_prefixed(&mut data, 1)?).ok()?;
+        Some((address, parse_bytes(&mut data, 2)?))
+    }
+
+    pub fn parse_execute(data: &[u8]) -> Option<Option<Binary>> {
+        let mut data = data.to_vec();
+        parse_bytes(&mut data, 1)
+    }
+}
+
+fn encode_field(out: &mut Vec<u8>, field_number: u8, value: &[u8]) {
+    out.push((field_number << 3) | 2);
+    let mut len = value.len();
+    while len >= 0x80 {
+        out.push((len as u8) | 0x80);
+        len >>= 7;
+    }
+    out.push(len as u8);
+    out.extend_from_slice(value);
+}
+
+fn instantiate_response(payload: &[u8]) -> Vec<u8> {
+    let mut out = vec![];
+    encode_field(
+        &mut out,
+        1,
+        b"cosmos1contractaddressxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx",
+    );
+    encode_field(&mut out, 2, payload);
+    out
+}
+
+/// execute responses wrapping each other `depth` times
+fn nested_execute_response(payload: &[u8], depth: usize) -> Vec<u8> {
+    (0..depth).fold(payload.to_vec(), |inner, _| {
+        let mut out = vec![];
+        encode_field(&mut out, 1, &inner);
+        out
+    })
+}
+
+fn reply(data: &[u8]) -> Reply {
+    Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(Binary(data.to_vec())),
+        }),
+    }
+}
+
+fn measure(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn report(name: &str, size: usize, baseline: Duration, current: Duration) {
+    println!(
+        "{:<22} {:>7} bytes   copying {:>12?}   borrowed {:>12?}   speedup {:>6.1}x",
+        name,
+        size,
+        baseline,
+        current,
+        baseline.as_secs_f64() / current.as_secs_f64().max(f64::EPSILON)
+    );
+}
+
+fn main() {
+    for size in [1024, 16 * 1024, 64 * 1024] {
+        let payload: Vec<u8> = (0..size).map(|i| i as u8).collect();
+
+        let data = instantiate_response(&payload);
+        let baseline = measure(|| {
+            black_box(copying::parse_instantiate(&data).unwrap());
+        });
+        let current = measure(|| {
+            black_box(parse_instantiate_response_data(&data).unwrap());
+        });
+        report("instantiate data", data.len(), baseline, current);
+
+        // both include cloning the reply, as parse_reply_* takes it by value
+        let msg = reply(&data);
+        let baseline = measure(|| {
+            let msg = msg.clone();
+            let data = msg.result.into_result().unwrap().data.unwrap();
+            black_box(copying::parse_instantiate(&data).unwrap());
+        });
+        let current = measure(|| {
+            black_box(parse_reply_instantiate_data(msg.clone()).unwrap());
+        });
+        report("instantiate reply", data.len(), baseline, current);
+
+        let data = nested_execute_response(&payload, 16);
+        let baseline = measure(|| {
+            let mut data = Binary(data.clone());
+            for _ in 0..16 {
+                data = copying::parse_execute(&data).unwrap().unwrap();
+            }
+            black_box(data);
+        });
+        let current = measure(|| {
+            let mut data = Binary(data.clone());
+            for _ in 0..16 {
+                data = parse_execute_response_data(&data).unwrap().data.unwrap();
+            }
+            black_box(data);
+        });
+        report("nested execute data", data.len(), baseline, current);
+
+        let msg = reply(&data);
+        let baseline = measure(|| {
+            let msg = msg.clone();
+            let mut data = msg.result.into_result().unwrap().data.unwrap();
+            for _ in 0..16 {
+                data = copying::parse_execute(&data).unwrap().unwrap();
+            }
+            black_box(data);
+        });
+        let current = measure(|| {
+            let mut data = parse_reply_execute_data(msg.clone()).unwrap().data.unwrap();
+            for _ in 1..16 {
+                data = parse_execute_response_data(&data).unwrap().data.unwrap();
+            }
+            black_box(data);
+        });
+        report("nested execute reply", data.len(), baseline, current);
+    }
+}
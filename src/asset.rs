@@ -0,0 +1,102 @@
+use std::fmt;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Coin, Uint128};
+
+/// AssetInfo identifies a token, either a native denom or a cw20 contract
+#[cw_serde]
+pub enum AssetInfo {
+    Native(String),
+    Cw20(Addr),
+}
+
+impl AssetInfo {
+    pub fn is_native(&self) -> bool {
+        matches!(self, AssetInfo::Native(_))
+    }
+}
+
+impl fmt::Display for AssetInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetInfo::Native(denom) => write!(f, "{}", denom),
+            AssetInfo::Cw20(addr) => write!(f, "cw20:{}", addr),
+        }
+    }
+}
+
+/// Asset is an amount of a native or cw20 token
+#[cw_serde]
+pub struct Asset {
+    pub info: AssetInfo,
+    pub amount: Uint128,
+}
+
+impl Asset {
+    pub fn native(amount: impl Into<Uint128>, denom: impl Into<String>) -> Self {
+        Asset {
+            info: AssetInfo::Native(denom.into()),
+            amount: amount.into(),
+        }
+    }
+
+    pub fn cw20(amount: impl Into<Uint128>, contract: Addr) -> Self {
+        Asset {
+            info: AssetInfo::Cw20(contract),
+            amount: amount.into(),
+        }
+    }
+}
+
+/// Cw20Amount is an amount of a cw20 token, identified by its validated contract address
+/// (unlike `cw20::Cw20Coin`, which holds an unvalidated string)
+#[cw_serde]
+pub struct Cw20Amount {
+    pub address: Addr,
+    pub amount: Uint128,
+}
+
+impl From<Cw20Amount> for Asset {
+    fn from(coin: Cw20Amount) -> Self {
+        Asset::cw20(coin.amount, coin.address)
+    }
+}
+
+impl From<Coin> for Asset {
+    fn from(coin: Coin) -> Self {
+        Asset::native(coin.amount, coin.denom)
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.amount, self.info)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cosmwasm_std::{coin, from_slice, to_vec};
+
+    #[test]
+    fn display_asset() {
+        assert_eq!(Asset::native(100u128, "uatom").to_string(), "100uatom");
+        assert_eq!(
+            Asset::cw20(5u128, Addr::unchecked("token")).to_string(),
+            "5cw20:token"
+        );
+        assert_eq!(Asset::from(coin(7, "wei")), Asset::native(7u128, "wei"));
+    }
+
+    #[test]
+    fn json_format() {
+        let asset = Asset::cw20(5u128, Addr::unchecked("token"));
+        let json = to_vec(&asset).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&json),
+            r#"{"info":{"cw20":"token"},"amount":"5"}"#
+        );
+        assert_eq!(from_slice::<Asset>(&json).unwrap(), asset);
+    }
+}
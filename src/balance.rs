@@ -1,12 +1,38 @@
-use std::{fmt, ops};
+use std::{borrow::Cow, cmp::Ordering, fmt, ops};
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Coin, OverflowError, OverflowOperation, StdError, StdResult, Uint128};
+use cosmwasm_std::{
+    to_binary, Addr, BankMsg, Coin, CosmosMsg, CustomQuery, Decimal, OverflowError,
+    OverflowOperation, QuerierWrapper, StdError, StdResult, Uint128, WasmMsg,
+};
+use schemars::JsonSchema;
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{Asset, AssetInfo, Cw20Amount};
+
+/// Where `NativeBalance::split_to` puts the dust left over from rounding
+#[cw_serde]
+#[derive(Copy)]
+pub enum SplitRemainder {
+    /// to the first recipient
+    First,
+    /// to the last recipient
+    Last,
+    /// to the recipient with the largest share (the first one on a tie)
+    Largest,
+    /// return it separately
+    Keep,
+}
 
 // Balance wraps Vec<Coin> and provides some nice helpers. It mutates the Vec and can be
 // unwrapped when done.
-#[cw_serde]
-#[derive(Default)]
+// Single coin operations (`has`, adding or subtracting a coin) use binary search, so they
+// require coins sorted by denom without duplicates, as they are when coming from the bank
+// module, `try_from_coins` or `deserialize_normalized`. Call `normalize` on other input first.
+// Operations on whole balances (adding, `checked_sub`, set operations, comparisons) accept
+// any input.
+// Not `cw_serde`, as equality compares the amounts per denom (see `PartialEq` below).
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema)]
 pub struct NativeBalance(pub Vec<Coin>);
 
 impl NativeBalance {
@@ -16,56 +42,301 @@ impl NativeBalance {
 
     /// returns true if the list of coins has at least the required amount
     pub fn has(&self, required: &Coin) -> bool {
-        self.0
-            .iter()
-            .find(|c| c.denom == required.denom)
-            .map(|m| m.amount >= required.amount)
+        self.find(&required.denom)
+            .map(|(_, m)| m.amount >= required.amount)
             .unwrap_or(false)
     }
 
-    /// normalize Wallet (sorted by denom, no 0 elements, no duplicate denoms)
+    /// Creates a normalized balance from untrusted coins. All denoms must be valid
+    /// Cosmos SDK denoms and may only appear once. Zero coins are dropped.
+    pub fn try_from_coins(coins: Vec<Coin>) -> StdResult<Self> {
+        for coin in coins.iter() {
+            validate_denom(&coin.denom)?;
+        }
+        let mut balance = NativeBalance(coins);
+        balance.0.retain(|c| !c.amount.is_zero());
+        balance.0.sort_by(|a, b| a.denom.cmp(&b.denom));
+        if let Some(pair) = balance.0.windows(2).find(|p| p[0].denom == p[1].denom) {
+            return Err(StdError::generic_err(format!(
+                "Duplicate denom: {}",
+                pair[0].denom
+            )));
+        }
+        Ok(balance)
+    }
+
+    /// Deserializes untrusted JSON into a normalized balance, merging duplicate denoms.
+    /// Use as `#[serde(deserialize_with = "NativeBalance::deserialize_normalized")]`.
+    pub fn deserialize_normalized<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let coins = Vec::<Coin>::deserialize(deserializer)?;
+        for coin in coins.iter() {
+            validate_denom(&coin.denom).map_err(de::Error::custom)?;
+        }
+        let mut balance = NativeBalance(coins);
+        balance.try_normalize().map_err(de::Error::custom)?;
+        Ok(balance)
+    }
+
+    /// returns true if the balance is sorted by denom, without 0 elements or duplicate denoms
+    pub fn is_normalized(&self) -> bool {
+        self.is_sorted() && self.0.iter().all(|c| !c.amount.is_zero())
+    }
+
+    /// normalize Wallet (sorted by denom, no 0 elements, no duplicate denoms).
+    /// Duplicate denoms adding up to more than `Uint128::MAX` are saturated,
+    /// use `try_normalize` to reject them instead.
     pub fn normalize(&mut self) {
+        self.merge_duplicates();
+    }
+
+    /// Like `normalize`, but fails if duplicate denoms add up to more than `Uint128::MAX`.
+    /// The balance is still normalized (with saturated amounts) on error.
+    pub fn try_normalize(&mut self) -> StdResult<()> {
+        match self.merge_duplicates() {
+            Some(err) => Err(err.into()),
+            None => Ok(()),
+        }
+    }
+
+    // normalizes, saturating on overflow and returning the first overflow
+    fn merge_duplicates(&mut self) -> Option<OverflowError> {
         // drop 0's
-        self.0.retain(|c| c.amount.u128() != 0);
-        // sort
-        self.0.sort_unstable_by(|a, b| a.denom.cmp(&b.denom));
+        self.0.retain(|c| !c.amount.is_zero());
+        // sort (stable, so this is linear for already sorted balances)
+        self.0.sort_by(|a, b| a.denom.cmp(&b.denom));
+        // merge duplicate denoms into the first entry, in place
+        let mut overflow = None;
+        self.0.dedup_by(|c, first| {
+            if c.denom != first.denom {
+                return false;
+            }
+            first.amount = first.amount.checked_add(c.amount).unwrap_or_else(|err| {
+                overflow.get_or_insert(err);
+                Uint128::MAX
+            });
+            true
+        });
+        overflow
+    }
 
-        // find all i where (self[i-1].denom == self[i].denom).
-        let mut dups: Vec<usize> = self
-            .0
+    /// returns true if the balance is sorted by denom, without duplicate denoms
+    fn is_sorted(&self) -> bool {
+        self.0.windows(2).all(|pair| pair[0].denom < pair[1].denom)
+    }
+
+    /// find returns the position and coin of the given denom, using binary search
+    fn find(&self, denom: &str) -> Option<(usize, &Coin)> {
+        self.search(denom).ok().map(|i| (i, &self.0[i]))
+    }
+
+    /// search returns the position of denom, or Err with the position where it should be
+    /// inserted to keep the balance sorted
+    fn search(&self, denom: &str) -> Result<usize, usize> {
+        self.0.binary_search_by(|c| c.denom.as_str().cmp(denom))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.0.iter().any(|x| x.amount != Uint128::zero())
+    }
+
+    /// Creates a message sending all (non-zero) coins to `to_address`, or None if there are none
+    pub fn into_bank_msg(self, to_address: impl Into<String>) -> Option<CosmosMsg> {
+        let amount: Vec<Coin> = self.0.into_iter().filter(|c| !c.amount.is_zero()).collect();
+        if amount.is_empty() {
+            return None;
+        }
+        Some(
+            BankMsg::Send {
+                to_address: to_address.into(),
+                amount,
+            }
+            .into(),
+        )
+    }
+
+    /// Queries all bank balances of the given address
+    pub fn query_all<C: CustomQuery>(
+        querier: &QuerierWrapper<C>,
+        address: impl Into<String>,
+    ) -> StdResult<Self> {
+        let mut balance = NativeBalance(querier.query_all_balances(address)?);
+        balance.normalize();
+        Ok(balance)
+    }
+
+    /// Splits the balance proportionally between the given recipients, whose shares must
+    /// add up to one. Every coin is rounded down for each recipient, and the dust left
+    /// over is handled as given by `remainder`.
+    ///
+    /// Returns the balance of every recipient (in the given order), and what is left over.
+    /// The sum of both is always the original balance.
+    pub fn split_to(
+        &self,
+        shares: &[(Addr, Decimal)],
+        remainder: SplitRemainder,
+    ) -> StdResult<(Vec<(Addr, NativeBalance)>, NativeBalance)> {
+        if shares.is_empty() {
+            return Err(StdError::generic_err("Cannot split between no recipients"));
+        }
+        // sum the atomics, so the shares must add up to exactly one, without overflow
+        let total = shares
             .iter()
-            .enumerate()
-            .filter_map(|(i, c)| {
-                if i != 0 && c.denom == self.0[i - 1].denom {
-                    Some(i)
-                } else {
-                    None
-                }
-            })
+            .try_fold(Uint128::zero(), |total, (_, share)| {
+                total.checked_add(share.atomics())
+            })?;
+        if total != Decimal::one().atomics() {
+            return Err(StdError::generic_err("Shares must add up to one"));
+        }
+        let dust_receiver = match remainder {
+            SplitRemainder::First => Some(0),
+            SplitRemainder::Last => Some(shares.len() - 1),
+            // the first of the largest shares
+            SplitRemainder::Largest => shares
+                .iter()
+                .enumerate()
+                .rev()
+                .max_by_key(|(_, (_, share))| *share)
+                .map(|(i, _)| i),
+            SplitRemainder::Keep => None,
+        };
+
+        let mut splits: Vec<(Addr, NativeBalance)> = shares
+            .iter()
+            .map(|(addr, _)| (addr.clone(), NativeBalance::default()))
             .collect();
-        dups.reverse();
+        let mut left = NativeBalance::default();
+        let mut normalized = self.clone();
+        normalized.normalize();
+        for coin in normalized.0 {
+            let mut dust = coin.amount;
+            for ((_, share), (_, balance)) in shares.iter().zip(splits.iter_mut()) {
+                let amount = coin.amount * *share;
+                dust -= amount;
+                if !amount.is_zero() {
+                    balance.0.push(Coin {
+                        denom: coin.denom.clone(),
+                        amount,
+                    });
+                }
+            }
+            if !dust.is_zero() {
+                let dust = Coin {
+                    denom: coin.denom,
+                    amount: dust,
+                };
+                match dust_receiver {
+                    Some(i) => splits[i].1 += dust,
+                    None => left.0.push(dust),
+                }
+            }
+        }
+        Ok((splits, left))
+    }
 
-        // we go through the dups in reverse order (to avoid shifting indexes of other ones)
-        for dup in dups {
-            let add = self.0[dup].amount;
-            self.0[dup - 1].amount += add;
-            self.0.remove(dup);
+    /// returns true if the balance has at least the amount of every coin in `required`
+    pub fn contains_all(&self, required: &NativeBalance) -> bool {
+        let mut contains = true;
+        self.zip_amounts(required, |_, have, need| contains &= have >= need);
+        contains
+    }
+
+    /// Subtracts all coins of `other`, returning a new normalized balance.
+    /// Fails if any coin is missing or too low, leaving this balance untouched.
+    pub fn checked_sub(&self, other: &NativeBalance) -> StdResult<Self> {
+        let mut res = self.clone();
+        res.normalize();
+        let mut other = other.clone();
+        other.normalize();
+        for coin in other.0 {
+            res = (res - coin)?;
         }
+        Ok(res)
     }
 
-    fn find(&self, denom: &str) -> Option<(usize, &Coin)> {
-        self.0.iter().enumerate().find(|(_i, c)| c.denom == denom)
+    /// Returns the coins present in both balances, with the smaller amount (normalized)
+    pub fn intersection(&self, other: &NativeBalance) -> Self {
+        self.merge_with(other, |a, b| a.min(b))
     }
 
-    /// insert_pos should only be called when denom is not in the Wallet.
-    /// it returns the position where denom should be inserted at (via splice).
-    /// It returns None if this should be appended
-    fn insert_pos(&self, denom: &str) -> Option<usize> {
-        self.0.iter().position(|c| c.denom.as_str() >= denom)
+    /// Returns what this balance has more than `other`, per denom (normalized)
+    pub fn difference(&self, other: &NativeBalance) -> Self {
+        self.merge_with(other, |a, b| a.saturating_sub(b))
     }
 
-    pub fn is_empty(&self) -> bool {
-        !self.0.iter().any(|x| x.amount != Uint128::zero())
+    /// Returns the smaller amount of every denom (normalized).
+    /// This is the same as `intersection`.
+    pub fn min(&self, other: &NativeBalance) -> Self {
+        self.intersection(other)
+    }
+
+    /// Returns the larger amount of every denom in either balance (normalized)
+    pub fn max(&self, other: &NativeBalance) -> Self {
+        self.merge_with(other, |a, b| a.max(b))
+    }
+
+    // combines the amounts of every denom in either balance, and normalizes the result
+    fn merge_with(&self, other: &NativeBalance, op: impl Fn(Uint128, Uint128) -> Uint128) -> Self {
+        let mut res = NativeBalance::default();
+        self.zip_amounts(other, |denom, a, b| {
+            let amount = op(a, b);
+            if !amount.is_zero() {
+                res.0.push(Coin {
+                    denom: denom.to_string(),
+                    amount,
+                });
+            }
+        });
+        res
+    }
+
+    // walks both normalized balances together in denom order, calling `f` with the amounts
+    // of every denom in either of them (zero where it is missing)
+    fn zip_amounts(&self, other: &NativeBalance, mut f: impl FnMut(&str, Uint128, Uint128)) {
+        let (left, right) = (self.normalized(), other.normalized());
+        let mut left = left.0.iter().peekable();
+        let mut right = right.0.iter().peekable();
+        loop {
+            match (left.peek().copied(), right.peek().copied()) {
+                (Some(a), Some(b)) => match a.denom.cmp(&b.denom) {
+                    Ordering::Less => {
+                        f(&a.denom, a.amount, Uint128::zero());
+                        left.next();
+                    }
+                    Ordering::Greater => {
+                        f(&b.denom, Uint128::zero(), b.amount);
+                        right.next();
+                    }
+                    Ordering::Equal => {
+                        f(&a.denom, a.amount, b.amount);
+                        left.next();
+                        right.next();
+                    }
+                },
+                (Some(a), None) => {
+                    f(&a.denom, a.amount, Uint128::zero());
+                    left.next();
+                }
+                (None, Some(b)) => {
+                    f(&b.denom, Uint128::zero(), b.amount);
+                    right.next();
+                }
+                (None, None) => break,
+            }
+        }
+    }
+
+    // borrows the balance if it is normalized already, otherwise normalizes a copy
+    fn normalized(&self) -> Cow<'_, NativeBalance> {
+        if self.is_normalized() {
+            Cow::Borrowed(self)
+        } else {
+            let mut balance = self.clone();
+            balance.normalize();
+            Cow::Owned(balance)
+        }
     }
 
     /// similar to `Balance.sub`, but doesn't fail when minuend less than subtrahend
@@ -91,6 +362,33 @@ impl NativeBalance {
     }
 }
 
+/// Balances are equal if they hold the same amount of every denom, even if their coins are
+/// in a different order, repeat a denom or include zero coins.
+impl PartialEq for NativeBalance {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+/// Orders balances by domination: a balance is greater than another one if it contains
+/// all of its coins, and more. Balances where each has more of some denom are not comparable.
+/// This agrees with `==`, so equivalent balances are `Equal`.
+impl PartialOrd for NativeBalance {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let (mut ge, mut le) = (true, true);
+        self.zip_amounts(other, |_, a, b| {
+            ge &= a >= b;
+            le &= a <= b;
+        });
+        match (ge, le) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Greater),
+            (false, true) => Some(Ordering::Less),
+            (false, false) => None,
+        }
+    }
+}
+
 impl fmt::Display for NativeBalance {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for c in &self.0 {
@@ -102,15 +400,12 @@ impl fmt::Display for NativeBalance {
 
 impl ops::AddAssign<Coin> for NativeBalance {
     fn add_assign(&mut self, other: Coin) {
-        match self.find(&other.denom) {
-            Some((i, c)) => {
-                self.0[i].amount = c.amount + other.amount;
+        match self.search(&other.denom) {
+            Ok(i) => {
+                self.0[i].amount += other.amount;
             }
             // place this in proper sorted order
-            None => match self.insert_pos(&other.denom) {
-                Some(idx) => self.0.insert(idx, other),
-                None => self.0.push(other),
-            },
+            Err(pos) => self.0.insert(pos, other),
         };
     }
 }
@@ -125,10 +420,41 @@ impl ops::Add<Coin> for NativeBalance {
 }
 
 impl ops::AddAssign<NativeBalance> for NativeBalance {
-    fn add_assign(&mut self, other: NativeBalance) {
-        for coin in other.0.into_iter() {
-            self.add_assign(coin);
+    fn add_assign(&mut self, mut other: NativeBalance) {
+        // the merge needs both sides sorted, which is a linear check like the merge itself
+        if !self.is_sorted() {
+            self.normalize();
         }
+        if !other.is_sorted() {
+            other.normalize();
+        }
+
+        // merge both sorted lists in one pass
+        let mut merged = Vec::with_capacity(self.0.len() + other.0.len());
+        let mut left = std::mem::take(&mut self.0).into_iter().peekable();
+        let mut right = other.0.into_iter().peekable();
+        loop {
+            let next = match (left.peek(), right.peek()) {
+                (Some(a), Some(b)) => match a.denom.cmp(&b.denom) {
+                    Ordering::Less => left.next(),
+                    Ordering::Greater => right.next(),
+                    Ordering::Equal => {
+                        let amount = right.next().map(|b| b.amount).unwrap_or_default();
+                        left.next().map(|mut a| {
+                            a.amount += amount;
+                            a
+                        })
+                    }
+                },
+                (Some(_), None) => left.next(),
+                (None, _) => right.next(),
+            };
+            match next {
+                Some(coin) => merged.push(coin),
+                None => break,
+            }
+        }
+        self.0 = merged;
     }
 }
 
@@ -170,19 +496,289 @@ impl ops::Sub<Coin> for NativeBalance {
 impl ops::Sub<Vec<Coin>> for NativeBalance {
     type Output = StdResult<Self>;
 
-    fn sub(self, amount: Vec<Coin>) -> StdResult<Self> {
-        let mut res = self;
+    fn sub(mut self, amount: Vec<Coin>) -> StdResult<Self> {
+        // subtract in place, and only drop the emptied coins at the end
         for coin in amount {
-            res = res.sub(coin.clone())?;
+            match self.find(&coin.denom) {
+                Some((i, c)) => self.0[i].amount = c.amount.checked_sub(coin.amount)?,
+                // error if no tokens
+                None => return Err(sub_overflow(coin.amount)),
+            }
+        }
+        self.0.retain(|c| !c.amount.is_zero());
+        Ok(self)
+    }
+}
+
+/// GenericBalance holds both native coins and cw20 tokens. It behaves like `NativeBalance`
+/// for both of them, and keeps the cw20 tokens sorted by address.
+#[cw_serde]
+#[derive(Default)]
+pub struct GenericBalance {
+    pub native: NativeBalance,
+    pub cw20: Vec<Cw20Amount>,
+}
+
+// the message cw20 contracts accept to transfer tokens
+#[cw_serde]
+enum Cw20ExecuteMsg {
+    Transfer { recipient: String, amount: Uint128 },
+}
+
+impl GenericBalance {
+    /// returns true if the balance has at least the required amount
+    pub fn has(&self, required: &Asset) -> bool {
+        match &required.info {
+            AssetInfo::Native(denom) => self.native.has(&Coin {
+                denom: denom.clone(),
+                amount: required.amount,
+            }),
+            AssetInfo::Cw20(address) => self
+                .find_cw20(address)
+                .map(|(_, c)| c.amount >= required.amount)
+                .unwrap_or(false),
+        }
+    }
+
+    /// normalize both native and cw20 tokens (sorted, no 0 elements, no duplicates).
+    /// Like `NativeBalance::normalize`, duplicates adding up to more than `Uint128::MAX`
+    /// are saturated.
+    pub fn normalize(&mut self) {
+        self.native.normalize();
+        self.cw20.retain(|c| !c.amount.is_zero());
+        self.cw20.sort_by(|a, b| a.address.cmp(&b.address));
+        let mut merged: Vec<Cw20Amount> = Vec::with_capacity(self.cw20.len());
+        for coin in self.cw20.drain(..) {
+            match merged.last_mut() {
+                Some(last) if last.address == coin.address => {
+                    last.amount = last.amount.saturating_add(coin.amount)
+                }
+                _ => merged.push(coin),
+            }
+        }
+        self.cw20 = merged;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.native.is_empty() && self.cw20.iter().all(|c| c.amount.is_zero())
+    }
+
+    /// similar to `GenericBalance.sub`, but doesn't fail when minuend less than subtrahend
+    pub fn sub_saturating(mut self, other: Asset) -> StdResult<Self> {
+        match other.info {
+            AssetInfo::Native(denom) => {
+                self.native = self.native.sub_saturating(Coin {
+                    denom,
+                    amount: other.amount,
+                })?;
+            }
+            AssetInfo::Cw20(address) => match self.find_cw20(&address) {
+                Some((i, c)) => {
+                    if c.amount <= other.amount {
+                        self.cw20.remove(i);
+                    } else {
+                        self.cw20[i].amount -= other.amount;
+                    }
+                }
+                // error if no tokens
+                None => return Err(sub_overflow(other.amount)),
+            },
+        }
+        Ok(self)
+    }
+
+    /// Creates the messages to send the whole balance to `recipient`:
+    /// one bank send for all native coins, and one transfer per cw20 token.
+    pub fn into_msgs(self, recipient: impl Into<String>) -> StdResult<Vec<CosmosMsg>> {
+        let recipient = recipient.into();
+        let native: Vec<Coin> = self
+            .native
+            .into_vec()
+            .into_iter()
+            .filter(|c| !c.amount.is_zero())
+            .collect();
+        let mut msgs = vec![];
+        if !native.is_empty() {
+            msgs.push(
+                BankMsg::Send {
+                    to_address: recipient.clone(),
+                    amount: native,
+                }
+                .into(),
+            );
+        }
+        for coin in self.cw20.into_iter().filter(|c| !c.amount.is_zero()) {
+            msgs.push(
+                WasmMsg::Execute {
+                    contract_addr: coin.address.into_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: recipient.clone(),
+                        amount: coin.amount,
+                    })?,
+                    funds: vec![],
+                }
+                .into(),
+            );
+        }
+        Ok(msgs)
+    }
+
+    fn find_cw20(&self, address: &Addr) -> Option<(usize, &Cw20Amount)> {
+        self.cw20
+            .iter()
+            .enumerate()
+            .find(|(_i, c)| &c.address == address)
+    }
+}
+
+/// Checks the denom against the Cosmos SDK format: `[a-zA-Z][a-zA-Z0-9/:._-]{2,127}`
+fn validate_denom(denom: &str) -> StdResult<()> {
+    let valid_start = denom.starts_with(|c: char| c.is_ascii_alphabetic());
+    let valid_chars = denom
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | ':' | '.' | '_' | '-'));
+    if valid_start && valid_chars && (3..=128).contains(&denom.len()) {
+        Ok(())
+    } else {
+        Err(StdError::generic_err(format!("Invalid denom: {}", denom)))
+    }
+}
+
+fn sub_overflow(amount: Uint128) -> StdError {
+    StdError::overflow(OverflowError::new(OverflowOperation::Sub, 0, amount.u128()))
+}
+
+impl From<NativeBalance> for GenericBalance {
+    fn from(native: NativeBalance) -> Self {
+        GenericBalance {
+            native,
+            cw20: vec![],
+        }
+    }
+}
+
+impl fmt::Display for GenericBalance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.native)?;
+        for c in &self.cw20 {
+            write!(f, "{}{}", c.address, c.amount)?
+        }
+        Ok(())
+    }
+}
+
+impl ops::AddAssign<Asset> for GenericBalance {
+    fn add_assign(&mut self, other: Asset) {
+        match other.info {
+            AssetInfo::Native(denom) => {
+                self.native += Coin {
+                    denom,
+                    amount: other.amount,
+                }
+            }
+            AssetInfo::Cw20(address) => match self.find_cw20(&address) {
+                Some((i, c)) => {
+                    self.cw20[i].amount = c.amount + other.amount;
+                }
+                // place this in proper sorted order
+                None => {
+                    let idx = self.cw20.partition_point(|c| c.address < address);
+                    self.cw20.insert(
+                        idx,
+                        Cw20Amount {
+                            address,
+                            amount: other.amount,
+                        },
+                    );
+                }
+            },
+        }
+    }
+}
+
+impl ops::Add<Asset> for GenericBalance {
+    type Output = Self;
+
+    fn add(mut self, other: Asset) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl ops::AddAssign<GenericBalance> for GenericBalance {
+    fn add_assign(&mut self, other: GenericBalance) {
+        self.native += other.native;
+        for coin in other.cw20.into_iter() {
+            self.add_assign(Asset::from(coin));
+        }
+    }
+}
+
+impl ops::Add<GenericBalance> for GenericBalance {
+    type Output = Self;
+
+    fn add(mut self, other: GenericBalance) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl ops::Sub<Asset> for GenericBalance {
+    type Output = StdResult<Self>;
+
+    fn sub(mut self, other: Asset) -> StdResult<Self> {
+        match other.info {
+            AssetInfo::Native(denom) => {
+                self.native = (self.native
+                    - Coin {
+                        denom,
+                        amount: other.amount,
+                    })?;
+            }
+            AssetInfo::Cw20(address) => match self.find_cw20(&address) {
+                Some((i, c)) => {
+                    let remainder = c.amount.checked_sub(other.amount)?;
+                    if remainder.is_zero() {
+                        self.cw20.remove(i);
+                    } else {
+                        self.cw20[i].amount = remainder;
+                    }
+                }
+                // error if no tokens
+                None => return Err(sub_overflow(other.amount)),
+            },
+        }
+        Ok(self)
+    }
+}
+
+impl ops::Sub<GenericBalance> for GenericBalance {
+    type Output = StdResult<Self>;
+
+    fn sub(self, other: GenericBalance) -> StdResult<Self> {
+        let mut res = (self - other.native.into_vec())?;
+        for coin in other.cw20 {
+            res = (res - Asset::from(coin))?;
         }
         Ok(res)
     }
 }
 
+impl ops::Sub<Vec<Coin>> for GenericBalance {
+    type Output = StdResult<Self>;
+
+    fn sub(mut self, amount: Vec<Coin>) -> StdResult<Self> {
+        self.native = (self.native - amount)?;
+        Ok(self)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use cosmwasm_std::coin;
+    use cosmwasm_std::testing::MockQuerier;
+    use cosmwasm_std::Empty;
+    use cosmwasm_std::{coin, from_slice};
 
     #[test]
     fn balance_has_works() {
@@ -298,10 +894,7 @@ mod test {
         // remove 0 value items and sort
         let mut balance = NativeBalance(vec![coin(123, "ETH"), coin(0, "BTC"), coin(8990, "ATOM")]);
         balance.normalize();
-        assert_eq!(
-            balance,
-            NativeBalance(vec![coin(8990, "ATOM"), coin(123, "ETH")])
-        );
+        assert_eq!(balance.0, vec![coin(8990, "ATOM"), coin(123, "ETH")]);
 
         // merge duplicate entries of same denom
         let mut balance = NativeBalance(vec![
@@ -311,9 +904,654 @@ mod test {
             coin(11, "BTC"),
         ]);
         balance.normalize();
+        assert_eq!(balance.0, vec![coin(800, "BTC"), coin(444, "ETH")]);
+    }
+
+    #[test]
+    fn balance_into_bank_msg() {
+        let balance = NativeBalance(vec![coin(0, "ATOM"), coin(555, "BTC")]);
+        assert_eq!(
+            balance.into_bank_msg("recipient"),
+            Some(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "recipient".to_string(),
+                amount: vec![coin(555, "BTC")],
+            }))
+        );
+        let empty = NativeBalance(vec![coin(0, "ATOM")]);
+        assert_eq!(empty.into_bank_msg("recipient"), None);
+        assert_eq!(NativeBalance::default().into_bank_msg("recipient"), None);
+    }
+
+    #[test]
+    fn balance_query_all() {
+        let querier = MockQuerier::<Empty>::new(&[(
+            "contract",
+            &[coin(12, "ETH"), coin(0, "BTC"), coin(3, "ATOM")],
+        )]);
+        let wrapper: QuerierWrapper = QuerierWrapper::new(&querier);
+        let balance = NativeBalance::query_all(&wrapper, "contract").unwrap();
         assert_eq!(
             balance,
-            NativeBalance(vec![coin(800, "BTC"), coin(444, "ETH")])
+            NativeBalance(vec![coin(3, "ATOM"), coin(12, "ETH")])
+        );
+        let balance = NativeBalance::query_all(&wrapper, "other").unwrap();
+        assert_eq!(balance, NativeBalance::default());
+    }
+
+    #[test]
+    fn balance_split_to() {
+        let (alice, bob, carl) = (
+            Addr::unchecked("alice"),
+            Addr::unchecked("bob"),
+            Addr::unchecked("carl"),
+        );
+        let balance = NativeBalance(vec![coin(100, "ATOM"), coin(7, "BTC"), coin(1, "ETH")]);
+        let shares = [
+            (alice.clone(), Decimal::percent(25)),
+            (bob.clone(), Decimal::percent(50)),
+            (carl.clone(), Decimal::percent(25)),
+        ];
+
+        let (splits, left) = balance.split_to(&shares, SplitRemainder::Keep).unwrap();
+        assert_eq!(
+            splits,
+            vec![
+                (
+                    alice.clone(),
+                    NativeBalance(vec![coin(25, "ATOM"), coin(1, "BTC")])
+                ),
+                (
+                    bob.clone(),
+                    NativeBalance(vec![coin(50, "ATOM"), coin(3, "BTC")])
+                ),
+                (
+                    carl.clone(),
+                    NativeBalance(vec![coin(25, "ATOM"), coin(1, "BTC")])
+                ),
+            ]
+        );
+        assert_eq!(left, NativeBalance(vec![coin(2, "BTC"), coin(1, "ETH")]));
+
+        let (splits, left) = balance.split_to(&shares, SplitRemainder::First).unwrap();
+        assert_eq!(
+            splits[0],
+            (
+                alice.clone(),
+                NativeBalance(vec![coin(25, "ATOM"), coin(3, "BTC"), coin(1, "ETH")])
+            )
+        );
+        assert_eq!(left, NativeBalance::default());
+        let (splits, _) = balance.split_to(&shares, SplitRemainder::Last).unwrap();
+        assert_eq!(
+            splits[2],
+            (
+                carl.clone(),
+                NativeBalance(vec![coin(25, "ATOM"), coin(3, "BTC"), coin(1, "ETH")])
+            )
+        );
+        let (splits, _) = balance.split_to(&shares, SplitRemainder::Largest).unwrap();
+        assert_eq!(
+            splits[1],
+            (
+                bob.clone(),
+                NativeBalance(vec![coin(50, "ATOM"), coin(5, "BTC"), coin(1, "ETH")])
+            )
+        );
+
+        // nothing is lost, for any mode
+        let odd = NativeBalance(vec![coin(1_000_003, "ATOM"), coin(u128::MAX, "BTC")]);
+        let shares = [
+            (alice.clone(), Decimal::permille(333)),
+            (bob.clone(), Decimal::permille(333)),
+            (carl.clone(), Decimal::permille(334)),
+        ];
+        for mode in [
+            SplitRemainder::First,
+            SplitRemainder::Last,
+            SplitRemainder::Largest,
+            SplitRemainder::Keep,
+        ] {
+            let (splits, left) = odd.split_to(&shares, mode).unwrap();
+            let mut sum = left;
+            for (_, balance) in splits {
+                sum += balance;
+            }
+            assert_eq!(sum, odd);
+        }
+
+        // shares must add up to one
+        let err = balance
+            .split_to(&shares[..2], SplitRemainder::Keep)
+            .unwrap_err();
+        assert_eq!(err, StdError::generic_err("Shares must add up to one"));
+        balance.split_to(&[], SplitRemainder::Keep).unwrap_err();
+    }
+
+    #[test]
+    fn balance_contains_all() {
+        let balance = NativeBalance(vec![coin(555, "BTC"), coin(12345, "ETH")]);
+
+        assert!(balance.contains_all(&NativeBalance::default()));
+        assert!(balance.contains_all(&balance));
+        assert!(balance.contains_all(&NativeBalance(vec![coin(12345, "ETH")])));
+        assert!(balance.contains_all(&NativeBalance(vec![coin(1, "ETH"), coin(0, "ATOM")])));
+        assert!(!balance.contains_all(&NativeBalance(vec![coin(12346, "ETH")])));
+        assert!(!balance.contains_all(&NativeBalance(vec![coin(1, "ETH"), coin(1, "ATOM")])));
+        // duplicates are summed up
+        assert!(!balance.contains_all(&NativeBalance(vec![coin(300, "BTC"), coin(300, "BTC")])));
+    }
+
+    #[test]
+    fn balance_checked_sub() {
+        let balance = NativeBalance(vec![coin(555, "BTC"), coin(12345, "ETH")]);
+
+        let res = balance
+            .checked_sub(&NativeBalance(vec![coin(345, "ETH"), coin(555, "BTC")]))
+            .unwrap();
+        assert_eq!(res, NativeBalance(vec![coin(12000, "ETH")]));
+
+        // fails on the second coin, without changing anything
+        balance
+            .checked_sub(&NativeBalance(vec![coin(5, "BTC"), coin(12346, "ETH")]))
+            .unwrap_err();
+        balance
+            .checked_sub(&NativeBalance(vec![coin(5, "BTC"), coin(1, "ATOM")]))
+            .unwrap_err();
+        assert_eq!(
+            balance,
+            NativeBalance(vec![coin(555, "BTC"), coin(12345, "ETH")])
+        );
+    }
+
+    #[test]
+    fn balance_set_operations() {
+        let a = NativeBalance(vec![coin(5, "ATOM"), coin(555, "BTC"), coin(100, "ETH")]);
+        let b = NativeBalance(vec![coin(200, "ETH"), coin(500, "BTC"), coin(7, "OSMO")]);
+
+        assert_eq!(
+            a.intersection(&b),
+            NativeBalance(vec![coin(500, "BTC"), coin(100, "ETH")])
+        );
+        assert_eq!(a.min(&b), a.intersection(&b));
+        assert_eq!(
+            a.max(&b),
+            NativeBalance(vec![
+                coin(5, "ATOM"),
+                coin(555, "BTC"),
+                coin(200, "ETH"),
+                coin(7, "OSMO")
+            ])
+        );
+        assert_eq!(
+            a.difference(&b),
+            NativeBalance(vec![coin(5, "ATOM"), coin(55, "BTC")])
+        );
+        assert_eq!(
+            b.difference(&a),
+            NativeBalance(vec![coin(100, "ETH"), coin(7, "OSMO")])
+        );
+        // the difference and the intersection add up to the original
+        let mut sum = a.difference(&b) + a.intersection(&b);
+        sum.normalize();
+        assert_eq!(sum, a);
+
+        let empty = NativeBalance::default();
+        assert_eq!(a.intersection(&empty), empty);
+        assert_eq!(a.difference(&empty), a);
+        assert_eq!(empty.max(&a), a);
+
+        // large amounts of the same denom never get added
+        let big = NativeBalance(vec![coin(u128::MAX, "BTC"), coin(u128::MAX - 1, "ETH")]);
+        let other = NativeBalance(vec![coin(u128::MAX - 1, "BTC"), coin(u128::MAX, "ETH")]);
+        assert_eq!(
+            big.max(&other),
+            NativeBalance(vec![coin(u128::MAX, "BTC"), coin(u128::MAX, "ETH")])
+        );
+        assert_eq!(
+            big.intersection(&other),
+            NativeBalance(vec![coin(u128::MAX - 1, "BTC"), coin(u128::MAX - 1, "ETH")])
+        );
+        assert_eq!(big.difference(&other), NativeBalance(vec![coin(1, "BTC")]));
+        assert!(big.contains_all(&NativeBalance(vec![coin(u128::MAX, "BTC")])));
+
+        // unsorted input with duplicates and zeros
+        let unsorted = NativeBalance(vec![
+            coin(100, "ETH"),
+            coin(0, "OSMO"),
+            coin(5, "ATOM"),
+            coin(500, "BTC"),
+            coin(55, "BTC"),
+        ]);
+        assert_eq!(unsorted.intersection(&b), a.intersection(&b));
+        assert_eq!(b.difference(&unsorted), b.difference(&a));
+        assert_eq!(unsorted.max(&b), a.max(&b));
+    }
+
+    #[test]
+    fn balance_partial_order() {
+        let small = NativeBalance(vec![coin(5, "BTC")]);
+        let big = NativeBalance(vec![coin(5, "BTC"), coin(1, "ETH")]);
+        let other = NativeBalance(vec![coin(6, "BTC")]);
+
+        assert!(small < big);
+        assert!(big > small);
+        assert!(small <= small.clone());
+        assert!(small < other);
+        // each one has more of something
+        assert_eq!(big.partial_cmp(&other), None);
+
+        // same amounts with zero coins or in another order are equivalent
+        let with_zero = NativeBalance(vec![coin(0, "ATOM"), coin(5, "BTC")]);
+        assert_eq!(small.partial_cmp(&with_zero), Some(Ordering::Equal));
+        assert!(small <= with_zero);
+        assert!(with_zero <= small);
+        let reordered = NativeBalance(vec![coin(1, "ETH"), coin(5, "BTC")]);
+        assert_eq!(big.partial_cmp(&reordered), Some(Ordering::Equal));
+        assert!(reordered <= big);
+        assert!(big <= reordered);
+        assert!(with_zero < reordered);
+        let split = NativeBalance(vec![coin(1, "ETH"), coin(2, "BTC"), coin(3, "BTC")]);
+        assert_eq!(split.partial_cmp(&big), Some(Ordering::Equal));
+        // and so is ==
+        assert_eq!(small, with_zero);
+        assert_eq!(big, reordered);
+        assert_eq!(split, big);
+        assert_ne!(small, big);
+        assert_ne!(big, other);
+
+        // escrow release check
+        let escrow = NativeBalance(vec![coin(100, "ATOM"), coin(50, "ETH")]);
+        let release = NativeBalance(vec![coin(50, "ETH")]);
+        assert!(release <= escrow);
+    }
+
+    #[test]
+    fn balance_try_from_coins() {
+        let balance = NativeBalance::try_from_coins(vec![
+            coin(5, "uosmo"),
+            coin(0, "BTC"),
+            coin(
+                3,
+                "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+            ),
+            coin(7, "factory/osmo1abc/sub.denom_x-1:2"),
+        ])
+        .unwrap();
+        assert!(balance.is_normalized());
+        assert_eq!(balance.0.len(), 3);
+        assert_eq!(balance.0[0].denom, "factory/osmo1abc/sub.denom_x-1:2");
+
+        let err =
+            NativeBalance::try_from_coins(vec![coin(1, "uatom"), coin(2, "uatom")]).unwrap_err();
+        assert_eq!(err, StdError::generic_err("Duplicate denom: uatom"));
+
+        for denom in ["ab", "1atom", "/uatom", "u atom", "uatöm", &"a".repeat(129)] {
+            let err = NativeBalance::try_from_coins(vec![coin(1, denom)]).unwrap_err();
+            assert_eq!(
+                err,
+                StdError::generic_err(format!("Invalid denom: {}", denom))
+            );
+        }
+        NativeBalance::try_from_coins(vec![coin(1, "a".repeat(128))]).unwrap();
+        assert_eq!(
+            NativeBalance::try_from_coins(vec![]).unwrap(),
+            NativeBalance::default()
+        );
+    }
+
+    #[test]
+    fn balance_normalize_overflow() {
+        let coins = vec![coin(u128::MAX, "BTC"), coin(1, "ATOM"), coin(2, "BTC")];
+        let mut saturated = NativeBalance(coins.clone());
+        saturated.normalize();
+        assert_eq!(saturated.0, vec![coin(1, "ATOM"), coin(u128::MAX, "BTC")]);
+
+        let mut balance = NativeBalance(coins);
+        let err = balance.try_normalize().unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        assert_eq!(balance.0, saturated.0);
+
+        let mut balance = NativeBalance(vec![coin(2, "BTC"), coin(1, "ATOM"), coin(2, "BTC")]);
+        balance.try_normalize().unwrap();
+        assert_eq!(balance.0, vec![coin(1, "ATOM"), coin(4, "BTC")]);
+    }
+
+    #[test]
+    fn balance_is_normalized() {
+        assert!(NativeBalance::default().is_normalized());
+        assert!(NativeBalance(vec![coin(1, "ATOM"), coin(2, "BTC")]).is_normalized());
+        assert!(!NativeBalance(vec![coin(2, "BTC"), coin(1, "ATOM")]).is_normalized());
+        assert!(!NativeBalance(vec![coin(1, "ATOM"), coin(2, "ATOM")]).is_normalized());
+        assert!(!NativeBalance(vec![coin(0, "ATOM"), coin(2, "BTC")]).is_normalized());
+    }
+
+    #[test]
+    fn balance_deserialize_normalized() {
+        #[cw_serde]
+        struct Msg {
+            #[serde(deserialize_with = "NativeBalance::deserialize_normalized")]
+            funds: NativeBalance,
+        }
+
+        let json = br#"{"funds":[{"denom":"uosmo","amount":"5"},{"denom":"uatom","amount":"0"},{"denom":"uosmo","amount":"2"},{"denom":"ujuno","amount":"1"}]}"#;
+        let msg: Msg = from_slice(json).unwrap();
+        assert_eq!(msg.funds.0, vec![coin(1, "ujuno"), coin(7, "uosmo")]);
+
+        let json = br#"{"funds":[{"denom":"u$d","amount":"5"}]}"#;
+        from_slice::<Msg>(json).unwrap_err();
+
+        // duplicates overflowing are an error, not a panic
+        let json = br#"{"funds":[{"denom":"uosmo","amount":"340282366920938463463374607431768211455"},{"denom":"uosmo","amount":"1"}]}"#;
+        let err = from_slice::<Msg>(json).unwrap_err();
+        assert!(err.to_string().contains("Overflow"), "{}", err);
+
+        // the plain format is kept as is
+        let json = br#"[{"denom":"uosmo","amount":"5"},{"denom":"uatom","amount":"1"}]"#;
+        assert!(!from_slice::<NativeBalance>(json).unwrap().is_normalized());
+    }
+
+    #[test]
+    fn balance_merge_matches_per_coin_addition() {
+        let denoms = ["ATOM", "BTC", "ETH", "ibc/27394FB0", "uosmo"];
+        let left: Vec<Coin> = denoms.iter().step_by(2).map(|d| coin(3, *d)).collect();
+        let right: Vec<Coin> = denoms.iter().skip(1).map(|d| coin(7, *d)).collect();
+
+        // sorted inputs take the merge path
+        let mut merged = NativeBalance(left.clone());
+        merged += NativeBalance(right.clone());
+        let mut expected = NativeBalance(left.clone());
+        for c in right.iter().cloned() {
+            expected += c;
+        }
+        assert_eq!(merged, expected);
+        assert_eq!(
+            merged,
+            NativeBalance(vec![
+                coin(3, "ATOM"),
+                coin(7, "BTC"),
+                coin(10, "ETH"),
+                coin(7, "ibc/27394FB0"),
+                coin(10, "uosmo"),
+            ])
+        );
+
+        // unsorted input falls back to adding coin by coin
+        let mut balance = NativeBalance(left);
+        balance += NativeBalance(right.into_iter().rev().collect());
+        assert_eq!(balance, merged);
+
+        // adding to or from empty
+        let mut empty = NativeBalance::default();
+        empty += merged.clone();
+        assert_eq!(empty, merged);
+        let mut same = merged.clone();
+        same += NativeBalance::default();
+        assert_eq!(same, merged);
+    }
+
+    #[test]
+    fn balance_unsorted_input() {
+        let unsorted = NativeBalance(vec![coin(12, "ETH"), coin(0, "BTC"), coin(3, "ATOM")]);
+        assert!(!unsorted.is_normalized());
+
+        // single coin operations work once normalized
+        let mut balance = unsorted.clone();
+        balance.normalize();
+        assert!(balance.has(&coin(3, "ATOM")));
+        assert!(balance.has(&coin(12, "ETH")));
+        assert!(!balance.has(&coin(1, "BTC")));
+        let res = (balance.clone() - coin(2, "ETH")).unwrap();
+        assert_eq!(res.0, vec![coin(3, "ATOM"), coin(10, "ETH")]);
+        let mut res = balance.clone();
+        res += coin(1, "DOGE");
+        assert_eq!(
+            res.0,
+            vec![coin(3, "ATOM"), coin(1, "DOGE"), coin(12, "ETH")]
+        );
+
+        // as do the ones from untrusted coins
+        let balance = NativeBalance::try_from_coins(unsorted.0.clone()).unwrap();
+        assert!(balance.has(&coin(3, "ATOM")));
+
+        // operations on whole balances accept any input
+        let mut res = unsorted.clone();
+        res += NativeBalance(vec![coin(1, "ETH"), coin(1, "ATOM")]);
+        assert_eq!(res.0, vec![coin(4, "ATOM"), coin(13, "ETH")]);
+        let mut res = NativeBalance(vec![coin(1, "ETH"), coin(1, "ATOM")]);
+        res += unsorted.clone();
+        assert_eq!(res.0, vec![coin(4, "ATOM"), coin(13, "ETH")]);
+        let res = unsorted
+            .checked_sub(&NativeBalance(vec![coin(2, "ETH"), coin(3, "ATOM")]))
+            .unwrap();
+        assert_eq!(res.0, vec![coin(10, "ETH")]);
+        assert!(unsorted.contains_all(&NativeBalance(vec![coin(3, "ATOM"), coin(1, "ETH")])));
+    }
+
+    #[test]
+    fn balance_normalize_merges_in_place() {
+        let mut balance = NativeBalance(vec![
+            coin(1, "ETH"),
+            coin(2, "ATOM"),
+            coin(0, "BTC"),
+            coin(3, "ETH"),
+            coin(4, "ATOM"),
+            coin(5, "ETH"),
+        ]);
+        balance.normalize();
+        assert_eq!(balance.0, vec![coin(6, "ATOM"), coin(9, "ETH")]);
+        assert!(balance.has(&coin(9, "ETH")));
+        assert!(!balance.has(&coin(1, "BTC")));
+    }
+
+    #[test]
+    fn balance_subtract_many_in_place() {
+        let balance = NativeBalance(vec![coin(5, "ATOM"), coin(3, "BTC"), coin(7, "ETH")]);
+        let res = (balance.clone() - vec![coin(3, "BTC"), coin(2, "ETH")]).unwrap();
+        assert_eq!(res, NativeBalance(vec![coin(5, "ATOM"), coin(5, "ETH")]));
+
+        // the same denom may be subtracted more than once
+        let res = (balance.clone() - vec![coin(2, "ETH"), coin(5, "ETH")]).unwrap();
+        assert_eq!(res, NativeBalance(vec![coin(5, "ATOM"), coin(3, "BTC")]));
+
+        (balance.clone() - vec![coin(1, "BTC"), coin(8, "ETH")]).unwrap_err();
+        (balance - vec![coin(1, "DOGE")]).unwrap_err();
+    }
+
+    fn cw20(amount: u128, address: &str) -> Asset {
+        Asset::cw20(amount, Addr::unchecked(address))
+    }
+
+    fn cw20_amount(amount: u128, address: &str) -> Cw20Amount {
+        Cw20Amount {
+            address: Addr::unchecked(address),
+            amount: Uint128::new(amount),
+        }
+    }
+
+    fn generic(native: Vec<Coin>, cw20: Vec<Cw20Amount>) -> GenericBalance {
+        GenericBalance {
+            native: NativeBalance(native),
+            cw20,
+        }
+    }
+
+    #[test]
+    fn generic_balance_has_works() {
+        let balance = generic(vec![coin(555, "BTC")], vec![cw20_amount(100, "token")]);
+
+        assert!(balance.has(&coin(555, "BTC").into()));
+        assert!(!balance.has(&coin(556, "BTC").into()));
+        assert!(balance.has(&cw20(100, "token")));
+        assert!(!balance.has(&cw20(101, "token")));
+        // wrong type
+        assert!(!balance.has(&cw20(1, "BTC")));
+        assert!(!balance.has(&coin(1, "token").into()));
+    }
+
+    #[test]
+    fn generic_balance_add_works() {
+        let balance = generic(vec![coin(555, "BTC")], vec![cw20_amount(100, "token")]);
+
+        let more = balance.clone() + cw20(50, "token") + Asset::from(coin(5, "BTC"));
+        assert_eq!(
+            more,
+            generic(vec![coin(560, "BTC")], vec![cw20_amount(150, "token")])
+        );
+
+        // new tokens are sorted in
+        let mut added = balance + cw20(1, "zzz") + cw20(2, "abc");
+        assert_eq!(
+            added,
+            generic(
+                vec![coin(555, "BTC")],
+                vec![
+                    cw20_amount(2, "abc"),
+                    cw20_amount(100, "token"),
+                    cw20_amount(1, "zzz")
+                ]
+            )
+        );
+
+        added += generic(vec![coin(1, "ATOM")], vec![cw20_amount(3, "abc")]);
+        assert_eq!(
+            added,
+            generic(
+                vec![coin(1, "ATOM"), coin(555, "BTC")],
+                vec![
+                    cw20_amount(5, "abc"),
+                    cw20_amount(100, "token"),
+                    cw20_amount(1, "zzz")
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn generic_balance_subtract_works() {
+        let balance = generic(vec![coin(555, "BTC")], vec![cw20_amount(100, "token")]);
+
+        let less = (balance.clone() - cw20(40, "token")).unwrap();
+        assert_eq!(
+            less,
+            generic(vec![coin(555, "BTC")], vec![cw20_amount(60, "token")])
+        );
+
+        // subtract all (and remove with 0 amount)
+        let none = (balance.clone() - cw20(100, "token")).unwrap();
+        assert_eq!(none, generic(vec![coin(555, "BTC")], vec![]));
+        let none = (none - Asset::from(coin(555, "BTC"))).unwrap();
+        assert!(none.is_empty());
+
+        // subtract more than we have, or a missing token
+        (balance.clone() - cw20(101, "token")).unwrap_err();
+        (balance.clone() - cw20(1, "other")).unwrap_err();
+        (balance.clone() - vec![coin(556, "BTC")]).unwrap_err();
+
+        // subtract a whole balance
+        let other = generic(vec![coin(55, "BTC")], vec![cw20_amount(10, "token")]);
+        assert_eq!(
+            (balance.clone() - other).unwrap(),
+            generic(vec![coin(500, "BTC")], vec![cw20_amount(90, "token")])
+        );
+        let other = generic(vec![], vec![cw20_amount(10, "other")]);
+        (balance - other).unwrap_err();
+    }
+
+    #[test]
+    fn generic_balance_subtract_saturating_works() {
+        let balance = generic(vec![coin(555, "BTC")], vec![cw20_amount(100, "token")]);
+
+        let less = balance.clone().sub_saturating(cw20(40, "token")).unwrap();
+        assert_eq!(
+            less,
+            generic(vec![coin(555, "BTC")], vec![cw20_amount(60, "token")])
+        );
+        let saturated = balance.clone().sub_saturating(cw20(400, "token")).unwrap();
+        assert_eq!(saturated, generic(vec![coin(555, "BTC")], vec![]));
+        let saturated = balance
+            .clone()
+            .sub_saturating(coin(1000, "BTC").into())
+            .unwrap();
+        assert_eq!(saturated, generic(vec![], vec![cw20_amount(100, "token")]));
+
+        // subtract non-existent token
+        balance
+            .clone()
+            .sub_saturating(cw20(1, "other"))
+            .unwrap_err();
+        balance.sub_saturating(coin(1, "ATOM").into()).unwrap_err();
+    }
+
+    #[test]
+    fn normalize_generic_balance() {
+        let mut balance = generic(
+            vec![coin(123, "ETH"), coin(0, "BTC"), coin(8990, "ATOM")],
+            vec![
+                cw20_amount(5, "zzz"),
+                cw20_amount(0, "nothing"),
+                cw20_amount(7, "abc"),
+                cw20_amount(6, "zzz"),
+            ],
+        );
+        assert!(!balance.is_empty());
+        balance.normalize();
+        assert_eq!(
+            balance,
+            generic(
+                vec![coin(8990, "ATOM"), coin(123, "ETH")],
+                vec![cw20_amount(7, "abc"), cw20_amount(11, "zzz")]
+            )
+        );
+        assert!(balance.native.is_normalized());
+
+        assert!(generic(vec![coin(0, "BTC")], vec![cw20_amount(0, "abc")]).is_empty());
+    }
+
+    #[test]
+    fn normalize_generic_balance_overflow() {
+        let mut balance = generic(
+            vec![coin(u128::MAX, "ETH"), coin(1, "ETH")],
+            vec![
+                cw20_amount(u128::MAX, "abc"),
+                cw20_amount(2, "zzz"),
+                cw20_amount(1, "abc"),
+            ],
+        );
+        balance.normalize();
+        assert_eq!(balance.native.0, vec![coin(u128::MAX, "ETH")]);
+        assert_eq!(
+            balance.cw20,
+            vec![cw20_amount(u128::MAX, "abc"), cw20_amount(2, "zzz")]
+        );
+    }
+
+    #[test]
+    fn generic_balance_into_msgs() {
+        let balance = generic(
+            vec![coin(123, "ETH"), coin(0, "BTC")],
+            vec![cw20_amount(7, "abc"), cw20_amount(0, "empty")],
+        );
+        let msgs = balance.into_msgs("recipient").unwrap();
+        assert_eq!(
+            msgs,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "recipient".to_string(),
+                    amount: vec![coin(123, "ETH")],
+                }),
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "abc".to_string(),
+                    msg: cosmwasm_std::Binary::from(
+                        br#"{"transfer":{"recipient":"recipient","amount":"7"}}"#.as_slice()
+                    ),
+                    funds: vec![],
+                }),
+            ]
+        );
+
+        assert_eq!(
+            GenericBalance::default().into_msgs("recipient").unwrap(),
+            vec![]
         );
     }
 }
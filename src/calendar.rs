@@ -0,0 +1,605 @@
+use std::fmt;
+use std::str::FromStr;
+
+use cosmwasm_std::{StdError, StdResult, Timestamp};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Scheduled;
+
+const SECONDS_PER_MINUTE: u64 = 60;
+const MINUTES_PER_DAY: u64 = 24 * 60;
+/// the last day a `Timestamp` can represent (in the year 2554)
+const MAX_DAY: u64 = u64::MAX / 1_000_000_000 / (SECONDS_PER_MINUTE * MINUTES_PER_DAY);
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// CalendarSchedule triggers on calendar dates, described by a cron expression
+/// (`minute hour day-of-month month day-of-week`), evaluated in UTC.
+///
+/// Every field accepts `*`, values, ranges (`1-5`), steps (`*/15`, `10-40/10`) and
+/// lists of those (`1,15`). Months and weekdays may also be given by their English
+/// three letter names, and Sunday is both `0` and `7`. The macros `@yearly`, `@monthly`,
+/// `@weekly`, `@daily` and `@hourly` are supported as well.
+///
+/// As in classic cron, if both day-of-month and day-of-week are restricted (not `*`),
+/// a day matching either one triggers.
+///
+/// It is (de)serialized as its cron expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CalendarSchedule {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+}
+
+impl CalendarSchedule {
+    /// Returns the first trigger strictly after the given time, or None if there is
+    /// none a `Timestamp` can represent.
+    pub fn next_trigger(&self, after: &Timestamp) -> Option<Scheduled> {
+        let next_minute = after.seconds() / SECONDS_PER_MINUTE + 1;
+        let mut day = next_minute / MINUTES_PER_DAY;
+        let mut minute_of_day = next_minute % MINUTES_PER_DAY;
+
+        while day <= MAX_DAY {
+            let (year, month, day_of_month) = civil_from_days(day);
+            if !has(self.months as u64, month) {
+                // skip to the first day of the next month
+                day += (days_in_month(year, month) - day_of_month + 1) as u64;
+                minute_of_day = 0;
+                continue;
+            }
+            if self.matches_day(day_of_month, weekday(day)) {
+                if let Some(minute) = self.first_minute_from(minute_of_day) {
+                    let seconds = (day * MINUTES_PER_DAY + minute) * SECONDS_PER_MINUTE;
+                    return seconds
+                        .checked_mul(1_000_000_000)
+                        .map(|nanos| Scheduled::AtTime(Timestamp::from_nanos(nanos)));
+                }
+            }
+            day += 1;
+            minute_of_day = 0;
+        }
+        None
+    }
+
+    /// Returns true if the schedule triggers at the minute containing the given time.
+    pub fn matches(&self, time: &Timestamp) -> bool {
+        let minutes = time.seconds() / SECONDS_PER_MINUTE;
+        let day = minutes / MINUTES_PER_DAY;
+        let minute_of_day = minutes % MINUTES_PER_DAY;
+        let (_, month, day_of_month) = civil_from_days(day);
+        has(self.months as u64, month)
+            && self.matches_day(day_of_month, weekday(day))
+            && has(self.hours as u64, (minute_of_day / 60) as u32)
+            && has(self.minutes, (minute_of_day % 60) as u32)
+    }
+
+    fn matches_day(&self, day_of_month: u32, weekday: u32) -> bool {
+        let any_day = self.days as u64 == Field::DAYS.all();
+        let any_weekday = self.weekdays as u64 == Field::WEEKDAYS.all();
+        let day_matches = has(self.days as u64, day_of_month);
+        let weekday_matches = has(self.weekdays as u64, weekday);
+        match (any_day, any_weekday) {
+            (false, false) => day_matches || weekday_matches,
+            _ => day_matches && weekday_matches,
+        }
+    }
+
+    // the first matching minute of the day at or after the given one
+    fn first_minute_from(&self, minute_of_day: u64) -> Option<u64> {
+        let (start_hour, start_minute) = (minute_of_day / 60, minute_of_day % 60);
+        (start_hour..24)
+            .filter(|&hour| has(self.hours as u64, hour as u32))
+            .find_map(|hour| {
+                let from = if hour == start_hour { start_minute } else { 0 };
+                (from..60)
+                    .find(|&minute| has(self.minutes, minute as u32))
+                    .map(|minute| hour * 60 + minute)
+            })
+    }
+}
+
+impl FromStr for CalendarSchedule {
+    type Err = StdError;
+
+    fn from_str(input: &str) -> StdResult<Self> {
+        let expression = match input.trim().to_ascii_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            "@weekly" => "0 0 * * 0".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@hourly" => "0 * * * *".to_string(),
+            expression => expression.to_string(),
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(parse_err(format!(
+                "expected 5 fields, got {} in '{}'",
+                fields.len(),
+                input
+            )));
+        }
+
+        let mut weekdays = Field::WEEKDAYS.parse(fields[4])?;
+        // 7 is another name for sunday
+        if has(weekdays, 7) {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        let schedule = CalendarSchedule {
+            minutes: Field::MINUTES.parse(fields[0])?,
+            hours: Field::HOURS.parse(fields[1])? as u32,
+            days: Field::DAYS.parse(fields[2])? as u32,
+            months: Field::MONTHS.parse(fields[3])? as u16,
+            weekdays: weekdays as u8,
+        };
+
+        // reject dates that never exist, like the 30th of February
+        let only_days = schedule.weekdays as u64 == Field::WEEKDAYS.all()
+            && schedule.days as u64 != Field::DAYS.all();
+        if only_days
+            && !(1..=12).any(|month| {
+                has(schedule.months as u64, month)
+                    && (1..=days_in_month(2000, month)).any(|day| has(schedule.days as u64, day))
+            })
+        {
+            return Err(parse_err(format!("'{}' never triggers", input)));
+        }
+        Ok(schedule)
+    }
+}
+
+impl fmt::Display for CalendarSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            Field::MINUTES.format(self.minutes),
+            Field::HOURS.format(self.hours as u64),
+            Field::DAYS.format(self.days as u64),
+            Field::MONTHS.format(self.months as u64),
+            Field::WEEKDAYS.format(self.weekdays as u64),
+        )
+    }
+}
+
+impl Serialize for CalendarSchedule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for CalendarSchedule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(CalendarScheduleVisitor)
+    }
+}
+
+struct CalendarScheduleVisitor;
+
+impl<'de> de::Visitor<'de> for CalendarScheduleVisitor {
+    type Value = CalendarSchedule;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("string-encoded cron expression")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(|e| E::custom(format!("{}", e)))
+    }
+}
+
+impl JsonSchema for CalendarSchedule {
+    fn schema_name() -> String {
+        "CalendarSchedule".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+/// The allowed values of a cron field, stored as a bitmask
+struct Field {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    names: &'static [&'static str],
+}
+
+impl Field {
+    const MINUTES: Field = Field::numeric("minute", 0, 59);
+    const HOURS: Field = Field::numeric("hour", 0, 23);
+    const DAYS: Field = Field::numeric("day of month", 1, 31);
+    const MONTHS: Field = Field {
+        name: "month",
+        min: 1,
+        max: 12,
+        names: &MONTH_NAMES,
+    };
+    // 7 is accepted while parsing and folded into 0
+    const WEEKDAYS: Field = Field {
+        name: "day of week",
+        min: 0,
+        max: 7,
+        names: &WEEKDAY_NAMES,
+    };
+
+    const fn numeric(name: &'static str, min: u32, max: u32) -> Field {
+        Field {
+            name,
+            min,
+            max,
+            names: &[],
+        }
+    }
+
+    // the last value that is stored (sunday is only stored as 0)
+    fn last(&self) -> u32 {
+        if self.names.len() == 7 {
+            6
+        } else {
+            self.max
+        }
+    }
+
+    fn all(&self) -> u64 {
+        (self.min..=self.last()).fold(0, |bits, value| bits | 1 << value)
+    }
+
+    fn parse(&self, input: &str) -> StdResult<u64> {
+        let mut bits = 0;
+        for part in input.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, Some(self.number(step)?)),
+                None => (part, None),
+            };
+            let (first, last) = match range.split_once('-') {
+                _ if range == "*" || range == "?" => (self.min, self.last()),
+                Some((first, last)) => (self.value(first)?, self.value(last)?),
+                // `5/10` means from 5 to the end, every 10
+                None if step.is_some() => (self.value(range)?, self.last()),
+                None => {
+                    let value = self.value(range)?;
+                    (value, value)
+                }
+            };
+            if first > last {
+                return Err(parse_err(format!("invalid {} range '{}'", self.name, part)));
+            }
+            let step = match step {
+                Some(0) => return Err(parse_err(format!("invalid {} step '{}'", self.name, part))),
+                Some(step) => step as usize,
+                None => 1,
+            };
+            bits = (first..=last)
+                .step_by(step)
+                .fold(bits, |bits, value| bits | 1 << value);
+        }
+        Ok(bits)
+    }
+
+    fn value(&self, input: &str) -> StdResult<u32> {
+        let value = match self.names.iter().position(|name| *name == input) {
+            Some(index) => index as u32 + self.min,
+            None => self.number(input)?,
+        };
+        if value < self.min || value > self.max {
+            return Err(parse_err(format!(
+                "{} must be between {} and {}, got '{}'",
+                self.name, self.min, self.max, input
+            )));
+        }
+        Ok(value)
+    }
+
+    // only ASCII digits, as `str::parse` also accepts a leading '+'
+    fn number(&self, input: &str) -> StdResult<u32> {
+        let invalid = || parse_err(format!("invalid {} '{}'", self.name, input));
+        if input.is_empty() || !input.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        input.parse().map_err(|_| invalid())
+    }
+
+    fn format(&self, bits: u64) -> String {
+        if bits == self.all() {
+            return "*".to_string();
+        }
+        let mut ranges = vec![];
+        let mut value = self.min;
+        while value <= self.last() {
+            if has(bits, value) {
+                let first = value;
+                while value < self.last() && has(bits, value + 1) {
+                    value += 1;
+                }
+                ranges.push(match value - first {
+                    0 => first.to_string(),
+                    1 => format!("{},{}", first, value),
+                    _ => format!("{}-{}", first, value),
+                });
+            }
+            value += 1;
+        }
+        ranges.join(",")
+    }
+}
+
+fn has(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+fn parse_err(msg: String) -> StdError {
+    StdError::parse_err("CalendarSchedule", msg)
+}
+
+fn is_leap_year(year: u64) -> bool {
+    match (year % 4, year % 100, year % 400) {
+        (_, _, 0) => true,
+        (_, 0, _) => false,
+        (0, _, _) => true,
+        _ => false,
+    }
+}
+
+fn days_in_month(year: u64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// 0 is sunday, 1970-01-01 was a thursday
+fn weekday(days_since_epoch: u64) -> u32 {
+    ((days_since_epoch + 4) % 7) as u32
+}
+
+/// Converts days since 1970-01-01 into (year, month, day).
+/// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days_since_epoch: u64) -> (u64, u32, u32) {
+    // shift the epoch to 0000-03-01, so leap days are at the end of the (400 year) era
+    let days = days_since_epoch + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cosmwasm_std::{from_slice, to_vec};
+
+    // seconds since epoch of the given UTC date and time
+    fn utc(year: u64, month: u32, day: u32, hour: u64, minute: u64) -> u64 {
+        // inverse of civil_from_days, only for testing
+        let mut days = 0;
+        while civil_from_days(days) != (year, month, day) {
+            days += 1;
+        }
+        (days * MINUTES_PER_DAY + hour * 60 + minute) * SECONDS_PER_MINUTE
+    }
+
+    fn next(schedule: &str, after: u64) -> Option<Scheduled> {
+        let schedule: CalendarSchedule = schedule.parse().unwrap();
+        schedule.next_trigger(&Timestamp::from_seconds(after))
+    }
+
+    fn at(seconds: u64) -> Option<Scheduled> {
+        Some(Scheduled::AtTime(Timestamp::from_seconds(seconds)))
+    }
+
+    #[test]
+    fn calendar_math() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(weekday(0), 4);
+        assert_eq!(civil_from_days(59), (1970, 3, 1));
+        // 2000 is a leap year
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(11017), (2000, 3, 1));
+        // 2100 is not
+        assert_eq!(civil_from_days(47540), (2100, 2, 28));
+        assert_eq!(civil_from_days(47541), (2100, 3, 1));
+        // 2023-01-01 was a sunday
+        assert_eq!(civil_from_days(19358), (2023, 1, 1));
+        assert_eq!(weekday(19358), 0);
+
+        // days are consecutive
+        let mut previous = civil_from_days(0);
+        for days in 1..200_000 {
+            let (year, month, day) = civil_from_days(days);
+            if day == 1 {
+                assert_eq!(previous.2, days_in_month(previous.0, previous.1));
+                if month == 1 {
+                    assert_eq!((previous.0 + 1, 12), (year, previous.1));
+                } else {
+                    assert_eq!((previous.0, previous.1 + 1), (year, month));
+                }
+            } else {
+                assert_eq!((previous.0, previous.1, previous.2 + 1), (year, month, day));
+            }
+            previous = (year, month, day);
+        }
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let cases = [
+            ("* * * * *", "* * * * *"),
+            ("0 0 1 * *", "0 0 1 * *"),
+            ("*/15 9-17 * * mon-fri", "0,15,30,45 9-17 * * 1-5"),
+            ("0 12 1,15 jan,JUL *", "0 12 1,15 1,7 *"),
+            ("5/20 0 * * 7", "5,25,45 0 * * 0"),
+            ("0 0 ? * 0-7", "0 0 * * *"),
+            ("10-40/10 1,2,3,7 * * sat,sun", "10-40/10 1-3,7 * * 0,6"),
+            ("@monthly", "0 0 1 * *"),
+            ("@weekly", "0 0 * * 0"),
+            ("@hourly", "0 * * * *"),
+        ];
+        for (input, display) in cases {
+            let schedule: CalendarSchedule = input.parse().unwrap();
+            // steps are expanded into lists
+            let expected: CalendarSchedule = display.parse().unwrap();
+            assert_eq!(schedule, expected, "{}", input);
+            // and display parses back to the same schedule
+            let reparsed: CalendarSchedule = schedule.to_string().parse().unwrap();
+            assert_eq!(schedule, reparsed, "{}", input);
+        }
+        assert_eq!(
+            "*/15 9-17 * * mon-fri"
+                .parse::<CalendarSchedule>()
+                .unwrap()
+                .to_string(),
+            "0,15,30,45 9-17 * * 1-5"
+        );
+
+        let invalid = [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "5-1 * * * *",
+            "*/0 * * * *",
+            "a * * * *",
+            "+5 * * * *",
+            "* 1-+3 * * *",
+            "*/+2 * * * *",
+            "* * * foo *",
+            "* * 30 feb *",
+            "* * 31 4,6,9,11 *",
+            "@never",
+        ];
+        for input in invalid {
+            input.parse::<CalendarSchedule>().unwrap_err();
+        }
+        // the 29th of february exists
+        "0 0 29 2 *".parse::<CalendarSchedule>().unwrap();
+    }
+
+    #[test]
+    fn next_trigger_works() {
+        let now = utc(2023, 5, 17, 13, 42) + 17;
+
+        // every minute
+        assert_eq!(next("* * * * *", now), at(utc(2023, 5, 17, 13, 43)));
+        // strictly after
+        assert_eq!(
+            next("* * * * *", utc(2023, 5, 17, 13, 43)),
+            at(utc(2023, 5, 17, 13, 44))
+        );
+        // first day of every month at midnight
+        assert_eq!(next("0 0 1 * *", now), at(utc(2023, 6, 1, 0, 0)));
+        assert_eq!(
+            next("0 0 1 * *", utc(2023, 12, 1, 0, 0)),
+            at(utc(2024, 1, 1, 0, 0))
+        );
+        // every monday (2023-05-17 is a wednesday)
+        assert_eq!(next("0 0 * * mon", now), at(utc(2023, 5, 22, 0, 0)));
+        // later today
+        assert_eq!(next("30 14 * * *", now), at(utc(2023, 5, 17, 14, 30)));
+        assert_eq!(next("50 13 * * *", now), at(utc(2023, 5, 17, 13, 50)));
+        // tomorrow
+        assert_eq!(next("30 13 * * *", now), at(utc(2023, 5, 18, 13, 30)));
+        // quarterly
+        assert_eq!(next("0 0 1 1,4,7,10 *", now), at(utc(2023, 7, 1, 0, 0)));
+        // day of month or day of week
+        assert_eq!(next("0 0 20 * fri", now), at(utc(2023, 5, 19, 0, 0)));
+        assert_eq!(next("0 0 18 * fri", now), at(utc(2023, 5, 18, 0, 0)));
+        assert_eq!(next("0 0 13 * *", now), at(utc(2023, 6, 13, 0, 0)));
+        // leap day
+        assert_eq!(next("0 0 29 2 *", now), at(utc(2024, 2, 29, 0, 0)));
+        assert_eq!(
+            next("0 0 29 2 *", utc(2096, 3, 1, 0, 0)),
+            at(utc(2104, 2, 29, 0, 0))
+        );
+        // the 31st skips short months
+        assert_eq!(
+            next("0 0 31 * *", utc(2023, 5, 31, 0, 0)),
+            at(utc(2023, 7, 31, 0, 0))
+        );
+        // @yearly at the end of the year
+        assert_eq!(
+            next("@yearly", utc(2023, 12, 31, 23, 59)),
+            at(utc(2024, 1, 1, 0, 0))
+        );
+    }
+
+    #[test]
+    fn next_trigger_matches() {
+        let schedules = ["*/7 */5 * * *", "0 0 1,15 * *", "0 6 * * sat", "3 3 3 3 3"];
+        for schedule in schedules {
+            let parsed: CalendarSchedule = schedule.parse().unwrap();
+            let mut time = Timestamp::from_seconds(utc(2023, 1, 1, 0, 0));
+            for _ in 0..20 {
+                let previous = time;
+                time = match parsed.next_trigger(&time).unwrap() {
+                    Scheduled::AtTime(time) => time,
+                    other => panic!("unexpected {}", other),
+                };
+                assert!(time > previous);
+                assert_eq!(time.seconds() % 60, 0);
+                assert!(parsed.matches(&time), "{} at {}", schedule, time);
+                // nothing in between
+                let mut minute = previous.plus_seconds(60 - previous.seconds() % 60);
+                while minute < time {
+                    assert!(!parsed.matches(&minute), "{} at {}", schedule, minute);
+                    minute = minute.plus_seconds(60);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn next_trigger_out_of_range() {
+        let last = Timestamp::from_nanos(u64::MAX);
+        assert_eq!(
+            "* * * * *"
+                .parse::<CalendarSchedule>()
+                .unwrap()
+                .next_trigger(&last),
+            None
+        );
+        let late = Timestamp::from_seconds(utc(2554, 1, 1, 0, 0));
+        assert_eq!(
+            "0 0 1 1 *"
+                .parse::<CalendarSchedule>()
+                .unwrap()
+                .next_trigger(&late),
+            None
+        );
+    }
+
+    #[test]
+    fn serde_as_string() {
+        let schedule: CalendarSchedule = "0 0 1 * *".parse().unwrap();
+        let json = to_vec(&schedule).unwrap();
+        assert_eq!(String::from_utf8_lossy(&json), r#""0 0 1 * *""#);
+        let parsed: CalendarSchedule = from_slice(br#""@monthly""#).unwrap();
+        assert_eq!(parsed, schedule);
+        from_slice::<CalendarSchedule>(br#""0 0 30 2 *""#).unwrap_err();
+    }
+}
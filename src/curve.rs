@@ -0,0 +1,507 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{BlockInfo, OverflowError, StdError, Uint128};
+use thiserror::Error;
+
+use crate::Duration;
+
+/// Curve is a value that changes along an `x` axis, usually the block height or time
+/// (see `value_at`). It can describe decaying voting power, emissions or unlocks.
+#[cw_serde]
+pub enum Curve {
+    /// The same value everywhere
+    Constant { y: Uint128 },
+    /// The line through `(min_x, min_y)` and `(max_x, max_y)`, continued in both directions.
+    /// It is clamped to the `Uint128` range.
+    Linear {
+        min_x: u64,
+        min_y: Uint128,
+        max_x: u64,
+        max_y: Uint128,
+    },
+    /// Linear between the given points (in strictly increasing `x` order),
+    /// and constant before the first and after the last one.
+    PiecewiseLinear { steps: Vec<(u64, Uint128)> },
+    /// `min_y` until `min_x`, then linear until `max_y` at `max_x`, and constant after that
+    Saturating {
+        min_x: u64,
+        min_y: Uint128,
+        max_x: u64,
+        max_y: Uint128,
+    },
+}
+
+impl Curve {
+    pub fn constant(y: u128) -> Self {
+        Curve::Constant { y: Uint128::new(y) }
+    }
+
+    pub fn saturating(min: (u64, u128), max: (u64, u128)) -> Self {
+        Curve::Saturating {
+            min_x: min.0,
+            min_y: Uint128::new(min.1),
+            max_x: max.0,
+            max_y: Uint128::new(max.1),
+        }
+    }
+
+    /// returns an error if the points of the curve are not in strictly increasing x order
+    pub fn validate(&self) -> Result<(), CurveError> {
+        match self {
+            Curve::Constant { .. } => Ok(()),
+            Curve::Linear { min_x, max_x, .. } | Curve::Saturating { min_x, max_x, .. } => {
+                if min_x >= max_x {
+                    return Err(CurveError::PointsOutOfOrder {});
+                }
+                Ok(())
+            }
+            Curve::PiecewiseLinear { steps } => {
+                if steps.is_empty() {
+                    return Err(CurveError::MissingSteps {});
+                }
+                if steps.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+                    return Err(CurveError::PointsOutOfOrder {});
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// returns an error if the curve is invalid, or if it ever decreases
+    pub fn validate_monotonic_increasing(&self) -> Result<(), CurveError> {
+        self.validate()?;
+        if self.ys().windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(CurveError::NotIncreasing {});
+        }
+        Ok(())
+    }
+
+    /// returns an error if the curve is invalid, or if it ever increases
+    pub fn validate_monotonic_decreasing(&self) -> Result<(), CurveError> {
+        self.validate()?;
+        if self.ys().windows(2).any(|pair| pair[0] < pair[1]) {
+            return Err(CurveError::NotDecreasing {});
+        }
+        Ok(())
+    }
+
+    /// Returns the value of the curve at the given x.
+    /// Fails if the curve is not valid (see `validate`), e.g. when it was deserialized from
+    /// untrusted input.
+    pub fn value(&self, x: u64) -> Result<Uint128, CurveError> {
+        self.validate()?;
+        Ok(self.value_unchecked(x))
+    }
+
+    /// Returns the value of the curve at the given block, using the block height or
+    /// time in seconds depending on `kind`
+    pub fn value_at(&self, kind: &Duration, block: &BlockInfo) -> Result<Uint128, CurveError> {
+        self.value(kind.block_position(block))
+    }
+
+    // the value at x, which panics if the curve is not valid
+    fn value_unchecked(&self, x: u64) -> Uint128 {
+        match self {
+            Curve::Constant { y } => *y,
+            Curve::Linear {
+                min_x,
+                min_y,
+                max_x,
+                max_y,
+            } => interpolate((*min_x, *min_y), (*max_x, *max_y), x),
+            Curve::Saturating {
+                min_x,
+                min_y,
+                max_x,
+                max_y,
+            } => {
+                let x = x.clamp(*min_x, *max_x);
+                interpolate((*min_x, *min_y), (*max_x, *max_y), x)
+            }
+            Curve::PiecewiseLinear { steps } => {
+                // the first point after x
+                let next = steps.partition_point(|(step_x, _)| *step_x <= x);
+                match (next, steps.get(next)) {
+                    (0, _) => steps[0].1,
+                    (_, None) => steps[next - 1].1,
+                    (_, Some(&after)) => interpolate(steps[next - 1], after, x),
+                }
+            }
+        }
+    }
+
+    /// Returns the lowest and highest value of the curve, which must be valid
+    pub fn range(&self) -> Result<(Uint128, Uint128), CurveError> {
+        self.validate()?;
+        let ys = match self {
+            Curve::Linear { .. } => vec![self.value_unchecked(0), self.value_unchecked(u64::MAX)],
+            _ => self.ys(),
+        };
+        let min = ys.iter().min().copied().unwrap_or_default();
+        let max = ys.iter().max().copied().unwrap_or_default();
+        Ok((min, max))
+    }
+
+    /// Returns a curve that is the sum of both curves.
+    ///
+    /// The result is exact at all points of both curves, and may be off by one in between
+    /// due to rounding. Linear curves cannot be combined, as their clamping is not linear.
+    pub fn combine(&self, other: &Curve) -> Result<Curve, CurveError> {
+        self.validate()?;
+        other.validate()?;
+        match (self, other) {
+            (Curve::Linear { .. }, _) | (_, Curve::Linear { .. }) => {
+                Err(CurveError::CannotCombineLinear {})
+            }
+            (Curve::Constant { y }, Curve::Constant { y: other }) => Ok(Curve::Constant {
+                y: y.checked_add(*other)?,
+            }),
+            (
+                Curve::Constant { y },
+                Curve::Saturating {
+                    min_x,
+                    min_y,
+                    max_x,
+                    max_y,
+                },
+            )
+            | (
+                Curve::Saturating {
+                    min_x,
+                    min_y,
+                    max_x,
+                    max_y,
+                },
+                Curve::Constant { y },
+            ) => Ok(Curve::Saturating {
+                min_x: *min_x,
+                min_y: min_y.checked_add(*y)?,
+                max_x: *max_x,
+                max_y: max_y.checked_add(*y)?,
+            }),
+            _ => {
+                let mut xs: Vec<u64> = self.xs().into_iter().chain(other.xs()).collect();
+                xs.sort_unstable();
+                xs.dedup();
+                let steps = xs
+                    .into_iter()
+                    .map(|x| {
+                        let y = self
+                            .value_unchecked(x)
+                            .checked_add(other.value_unchecked(x))?;
+                        Ok((x, y))
+                    })
+                    .collect::<Result<_, CurveError>>()?;
+                Ok(Curve::PiecewiseLinear { steps })
+            }
+        }
+    }
+
+    // the x values of all points defining the curve
+    fn xs(&self) -> Vec<u64> {
+        match self {
+            Curve::Constant { .. } => vec![0],
+            Curve::Linear { min_x, max_x, .. } | Curve::Saturating { min_x, max_x, .. } => {
+                vec![*min_x, *max_x]
+            }
+            Curve::PiecewiseLinear { steps } => steps.iter().map(|(x, _)| *x).collect(),
+        }
+    }
+
+    // the y values of all points defining the curve, in x order
+    fn ys(&self) -> Vec<Uint128> {
+        match self {
+            Curve::Constant { y } => vec![*y],
+            Curve::Linear { min_y, max_y, .. } | Curve::Saturating { min_y, max_y, .. } => {
+                vec![*min_y, *max_y]
+            }
+            Curve::PiecewiseLinear { steps } => steps.iter().map(|(_, y)| *y).collect(),
+        }
+    }
+}
+
+/// the value at x on the line through both points (with from.0 < to.0), clamped to Uint128
+fn interpolate(from: (u64, Uint128), to: (u64, Uint128), x: u64) -> Uint128 {
+    let width = to.0 - from.0;
+    let scale = |dy: Uint128, dx: u64| dy.checked_multiply_ratio(dx, width).unwrap_or(Uint128::MAX);
+    match (x >= from.0, to.1 >= from.1) {
+        (true, true) => from.1.saturating_add(scale(to.1 - from.1, x - from.0)),
+        (true, false) => from.1.saturating_sub(scale(from.1 - to.1, x - from.0)),
+        (false, true) => from.1.saturating_sub(scale(to.1 - from.1, from.0 - x)),
+        (false, false) => from.1.saturating_add(scale(from.1 - to.1, from.0 - x)),
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CurveError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Curve points must be in strictly increasing x order")]
+    PointsOutOfOrder {},
+
+    #[error("Piecewise linear curve needs at least one step")]
+    MissingSteps {},
+
+    #[error("Curve is not monotonically increasing")]
+    NotIncreasing {},
+
+    #[error("Curve is not monotonically decreasing")]
+    NotDecreasing {},
+
+    #[error("Linear curves cannot be combined")]
+    CannotCombineLinear {},
+}
+
+impl From<OverflowError> for CurveError {
+    fn from(err: OverflowError) -> Self {
+        CurveError::Std(StdError::overflow(err))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cosmwasm_std::{from_slice, OverflowOperation, Timestamp};
+
+    fn piecewise(steps: &[(u64, u128)]) -> Curve {
+        Curve::PiecewiseLinear {
+            steps: steps.iter().map(|(x, y)| (*x, Uint128::new(*y))).collect(),
+        }
+    }
+
+    fn linear(min: (u64, u128), max: (u64, u128)) -> Curve {
+        Curve::Linear {
+            min_x: min.0,
+            min_y: Uint128::new(min.1),
+            max_x: max.0,
+            max_y: Uint128::new(max.1),
+        }
+    }
+
+    fn values(curve: &Curve, xs: &[u64]) -> Vec<u128> {
+        xs.iter().map(|x| curve.value(*x).unwrap().u128()).collect()
+    }
+
+    #[test]
+    fn constant_curve() {
+        let curve = Curve::constant(17);
+        curve.validate().unwrap();
+        curve.validate_monotonic_increasing().unwrap();
+        curve.validate_monotonic_decreasing().unwrap();
+        assert_eq!(values(&curve, &[0, 10, u64::MAX]), vec![17, 17, 17]);
+        assert_eq!(curve.range().unwrap(), (Uint128::new(17), Uint128::new(17)));
+    }
+
+    #[test]
+    fn linear_curve() {
+        let curve = linear((10, 100), (20, 200));
+        curve.validate_monotonic_increasing().unwrap();
+        assert_eq!(
+            curve.validate_monotonic_decreasing().unwrap_err(),
+            CurveError::NotDecreasing {}
+        );
+        assert_eq!(
+            values(&curve, &[0, 5, 10, 15, 20, 30]),
+            vec![0, 50, 100, 150, 200, 300]
+        );
+        // clamped
+        assert_eq!(
+            curve.value(u64::MAX).unwrap(),
+            Uint128::new(u64::MAX as u128 * 10)
+        );
+        assert_eq!(
+            curve.range().unwrap(),
+            (Uint128::zero(), curve.value(u64::MAX).unwrap())
+        );
+
+        let curve = linear((10, 100), (20, 50));
+        curve.validate_monotonic_decreasing().unwrap();
+        assert_eq!(
+            curve.validate_monotonic_increasing().unwrap_err(),
+            CurveError::NotIncreasing {}
+        );
+        assert_eq!(
+            values(&curve, &[0, 10, 11, 20, 29, 30, 1000]),
+            vec![150, 100, 95, 50, 5, 0, 0]
+        );
+        assert_eq!(curve.range().unwrap(), (Uint128::zero(), Uint128::new(150)));
+
+        // does not overflow
+        let curve = linear((0, 0), (1, u128::MAX));
+        assert_eq!(curve.value(2).unwrap(), Uint128::MAX);
+
+        assert_eq!(
+            linear((10, 1), (10, 2)).validate().unwrap_err(),
+            CurveError::PointsOutOfOrder {}
+        );
+    }
+
+    #[test]
+    fn saturating_curve() {
+        let curve = Curve::saturating((10, 100), (20, 200));
+        curve.validate_monotonic_increasing().unwrap();
+        assert_eq!(
+            values(&curve, &[0, 10, 12, 20, 30, u64::MAX]),
+            vec![100, 100, 120, 200, 200, 200]
+        );
+        assert_eq!(
+            curve.range().unwrap(),
+            (Uint128::new(100), Uint128::new(200))
+        );
+
+        // decaying
+        let curve = Curve::saturating((0, 1000), (3, 0));
+        curve.validate_monotonic_decreasing().unwrap();
+        assert_eq!(values(&curve, &[0, 1, 2, 3, 4]), vec![1000, 667, 334, 0, 0]);
+
+        assert_eq!(
+            Curve::saturating((20, 1), (10, 2)).validate().unwrap_err(),
+            CurveError::PointsOutOfOrder {}
+        );
+    }
+
+    #[test]
+    fn piecewise_curve() {
+        let curve = piecewise(&[(10, 100), (20, 200), (30, 200), (40, 300)]);
+        curve.validate_monotonic_increasing().unwrap();
+        curve.validate_monotonic_decreasing().unwrap_err();
+        assert_eq!(
+            values(&curve, &[0, 10, 15, 20, 25, 30, 39, 40, 50]),
+            vec![100, 100, 150, 200, 200, 200, 290, 300, 300]
+        );
+        assert_eq!(
+            curve.range().unwrap(),
+            (Uint128::new(100), Uint128::new(300))
+        );
+
+        // up and down
+        let curve = piecewise(&[(0, 50), (10, 150), (20, 0)]);
+        curve.validate().unwrap();
+        curve.validate_monotonic_increasing().unwrap_err();
+        curve.validate_monotonic_decreasing().unwrap_err();
+        assert_eq!(values(&curve, &[5, 10, 15, 25]), vec![100, 150, 75, 0]);
+        assert_eq!(curve.range().unwrap(), (Uint128::zero(), Uint128::new(150)));
+
+        // a single step is constant
+        let curve = piecewise(&[(7, 3)]);
+        assert_eq!(values(&curve, &[0, 7, 100]), vec![3, 3, 3]);
+
+        assert_eq!(
+            piecewise(&[]).validate().unwrap_err(),
+            CurveError::MissingSteps {}
+        );
+        assert_eq!(
+            piecewise(&[(1, 1), (3, 3), (3, 4)]).validate().unwrap_err(),
+            CurveError::PointsOutOfOrder {}
+        );
+        assert_eq!(
+            piecewise(&[(5, 1), (3, 3)])
+                .validate_monotonic_increasing()
+                .unwrap_err(),
+            CurveError::PointsOutOfOrder {}
+        );
+    }
+
+    #[test]
+    fn invalid_curves_return_errors() {
+        // these deserialize fine, but must not panic when evaluated
+        let json = br#"{"piecewise_linear":{"steps":[]}}"#;
+        let curve: Curve = from_slice(json).unwrap();
+        assert_eq!(curve.value(0).unwrap_err(), CurveError::MissingSteps {});
+        assert_eq!(curve.range().unwrap_err(), CurveError::MissingSteps {});
+
+        let json = br#"{"piecewise_linear":{"steps":[[20,"1"],[10,"2"]]}}"#;
+        let curve: Curve = from_slice(json).unwrap();
+        assert_eq!(
+            curve.value(15).unwrap_err(),
+            CurveError::PointsOutOfOrder {}
+        );
+
+        let curve = Curve::saturating((20, 1), (10, 2));
+        assert_eq!(
+            curve.value(15).unwrap_err(),
+            CurveError::PointsOutOfOrder {}
+        );
+        let curve = linear((20, 1), (10, 2));
+        assert_eq!(
+            curve.value(15).unwrap_err(),
+            CurveError::PointsOutOfOrder {}
+        );
+        assert_eq!(curve.range().unwrap_err(), CurveError::PointsOutOfOrder {});
+    }
+
+    #[test]
+    fn value_at_block() {
+        let curve = Curve::saturating((100, 0), (200, 1000));
+        let block = BlockInfo {
+            height: 150,
+            time: Timestamp::from_seconds(120),
+            chain_id: "foo".to_string(),
+        };
+        assert_eq!(
+            curve.value_at(&Duration::Height(1), &block).unwrap(),
+            Uint128::new(500)
+        );
+        assert_eq!(
+            curve.value_at(&Duration::Time(1), &block).unwrap(),
+            Uint128::new(200)
+        );
+    }
+
+    #[test]
+    fn combine_curves() {
+        let constant = Curve::constant(10);
+        let saturating = Curve::saturating((10, 100), (20, 200));
+        let decaying = Curve::saturating((15, 100), (25, 0));
+        let steps = piecewise(&[(0, 0), (30, 300)]);
+
+        assert_eq!(constant.combine(&constant).unwrap(), Curve::constant(20));
+        assert_eq!(
+            constant.combine(&saturating).unwrap(),
+            Curve::saturating((10, 110), (20, 210))
+        );
+        assert_eq!(
+            saturating.combine(&constant).unwrap(),
+            Curve::saturating((10, 110), (20, 210))
+        );
+        assert_eq!(
+            saturating.combine(&decaying).unwrap(),
+            piecewise(&[(10, 200), (15, 250), (20, 250), (25, 200)])
+        );
+
+        // the sum matches at every point
+        let curves = [constant, saturating, decaying, steps];
+        for a in &curves {
+            for b in &curves {
+                let sum = a.combine(b).unwrap();
+                sum.validate().unwrap();
+                for x in 0..40 {
+                    assert_eq!(
+                        sum.value(x).unwrap(),
+                        a.value(x).unwrap() + b.value(x).unwrap(),
+                        "{:?} + {:?}",
+                        a,
+                        b
+                    );
+                }
+            }
+        }
+
+        // errors
+        let line = linear((0, 0), (1, 1));
+        assert_eq!(
+            line.combine(&curves[0]).unwrap_err(),
+            CurveError::CannotCombineLinear {}
+        );
+        assert_eq!(
+            curves[0].combine(&piecewise(&[])).unwrap_err(),
+            CurveError::MissingSteps {}
+        );
+        assert_eq!(
+            Curve::constant(u128::MAX)
+                .combine(&Curve::constant(1))
+                .unwrap_err(),
+            OverflowError::new(OverflowOperation::Add, Uint128::MAX, 1u128).into()
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{BlockInfo, StdError, StdResult, Timestamp};
+use cosmwasm_std::{BlockInfo, Decimal, StdError, StdResult, Timestamp, Uint128, Uint256, Uint64};
 use std::cmp::Ordering;
 use std::fmt;
 use std::ops::{Add, Mul};
+use std::str::FromStr;
 
 /// Expiration represents a point in time when some event happens.
 /// It can compare with a BlockInfo and will return is_expired() == true
@@ -14,6 +15,12 @@ pub enum Expiration {
     AtHeight(u64),
     /// AtTime will expire when `env.block.time` >= time
     AtTime(Timestamp),
+    /// AtHeightOrTime will expire when `env.block.height` >= height or `env.block.time` >= time,
+    /// whichever comes first
+    AtHeightOrTime { height: u64, time: Timestamp },
+    /// AtHeightAndTime will expire when `env.block.height` >= height and `env.block.time` >= time,
+    /// whichever comes last
+    AtHeightAndTime { height: u64, time: Timestamp },
     /// Never will never expire. Used to express the empty variant
     Never {},
 }
@@ -23,11 +30,52 @@ impl fmt::Display for Expiration {
         match self {
             Expiration::AtHeight(height) => write!(f, "expiration height: {}", height),
             Expiration::AtTime(time) => write!(f, "expiration time: {}", time),
+            Expiration::AtHeightOrTime { height, time } => {
+                write!(f, "expiration height: {} or time: {}", height, time)
+            }
+            Expiration::AtHeightAndTime { height, time } => {
+                write!(f, "expiration height: {} and time: {}", height, time)
+            }
             Expiration::Never {} => write!(f, "expiration: never"),
         }
     }
 }
 
+/// Parses an expiration like `"height 12345"`, `"time 1650000000"`,
+/// `"height 12345 or time 1650000000.5"`, `"height 12345 and time 1650000000"` or `"never"`.
+///
+/// Times are UNIX timestamps in seconds, with up to 9 fractional digits.
+/// Colons and an `expiration` prefix are ignored, so the `Display` format
+/// (e.g. `"expiration height: 12345"`) is accepted as well.
+impl FromStr for Expiration {
+    type Err = StdError;
+
+    fn from_str(input: &str) -> StdResult<Expiration> {
+        let normalized = input.replace(':', " ");
+        let mut tokens: Vec<&str> = normalized.split_whitespace().collect();
+        if tokens.first() == Some(&"expiration") {
+            tokens.remove(0);
+        }
+        match tokens.as_slice() {
+            ["never"] => Ok(Expiration::Never {}),
+            ["height", height] => Ok(Expiration::AtHeight(parse_u64(height, "Expiration")?)),
+            ["time", time] => Ok(Expiration::AtTime(parse_timestamp(time)?)),
+            ["height", height, "or", "time", time] => Ok(Expiration::AtHeightOrTime {
+                height: parse_u64(height, "Expiration")?,
+                time: parse_timestamp(time)?,
+            }),
+            ["height", height, "and", "time", time] => Ok(Expiration::AtHeightAndTime {
+                height: parse_u64(height, "Expiration")?,
+                time: parse_timestamp(time)?,
+            }),
+            _ => Err(StdError::parse_err(
+                "Expiration",
+                format!("invalid expiration '{}'", input),
+            )),
+        }
+    }
+}
+
 /// The default (empty value) is to never expire
 impl Default for Expiration {
     fn default() -> Self {
@@ -40,9 +88,114 @@ impl Expiration {
         match self {
             Expiration::AtHeight(height) => block.height >= *height,
             Expiration::AtTime(time) => block.time >= *time,
+            Expiration::AtHeightOrTime { height, time } => {
+                block.height >= *height || block.time >= *time
+            }
+            Expiration::AtHeightAndTime { height, time } => {
+                block.height >= *height && block.time >= *time
+            }
             Expiration::Never {} => false,
         }
     }
+
+    /// Converts this into an `AtTime` expiration, estimating the time of any height
+    pub fn to_time(&self, estimate: &BlockTimeEstimate) -> Expiration {
+        match self {
+            Expiration::AtHeight(height) => Expiration::AtTime(estimate.time_at(*height)),
+            Expiration::AtTime(time) => Expiration::AtTime(*time),
+            Expiration::AtHeightOrTime { height, time } => {
+                Expiration::AtTime(estimate.time_at(*height).min(*time))
+            }
+            Expiration::AtHeightAndTime { height, time } => {
+                Expiration::AtTime(estimate.time_at(*height).max(*time))
+            }
+            Expiration::Never {} => Expiration::Never {},
+        }
+    }
+
+    /// Converts this into an `AtHeight` expiration, estimating the height of any time
+    pub fn to_height(&self, estimate: &BlockTimeEstimate) -> Expiration {
+        match self {
+            Expiration::AtHeight(height) => Expiration::AtHeight(*height),
+            Expiration::AtTime(time) => Expiration::AtHeight(estimate.height_at(*time)),
+            Expiration::AtHeightOrTime { height, time } => {
+                Expiration::AtHeight(estimate.height_at(*time).min(*height))
+            }
+            Expiration::AtHeightAndTime { height, time } => {
+                Expiration::AtHeight(estimate.height_at(*time).max(*height))
+            }
+            Expiration::Never {} => Expiration::Never {},
+        }
+    }
+
+    /// Compares two expirations of any kind, by estimating when they will expire.
+    /// This is a total order (with ties) that never contradicts `partial_cmp`, but it may
+    /// return `Equal` where that returns `Less` or `Greater`, if both expire at the
+    /// same estimated height and time.
+    pub fn compare_with(&self, other: &Expiration, estimate: &BlockTimeEstimate) -> Ordering {
+        let key = |expiration: &Expiration| match (
+            expiration.to_time(estimate),
+            expiration.to_height(estimate),
+        ) {
+            (Expiration::AtTime(time), Expiration::AtHeight(height)) => Some((time, height)),
+            _ => None,
+        };
+        match (key(self), key(other)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            // never is later than anything
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    /// returns true if self is guaranteed to expire no later than other,
+    /// no matter how heights and times progress
+    fn expires_no_later_than(&self, other: &Expiration) -> bool {
+        use Expiration::*;
+        match (self, other) {
+            (_, Never {}) => true,
+            (Never {}, _) => false,
+            (AtHeight(h1), AtHeight(h2)) => h1 <= h2,
+            (AtHeight(h1), AtHeightAndTime { height: h2, .. }) => h1 <= h2,
+            (AtTime(t1), AtTime(t2)) => t1 <= t2,
+            (AtTime(t1), AtHeightAndTime { time: t2, .. }) => t1 <= t2,
+            (AtHeightOrTime { height: h1, .. }, AtHeight(h2)) => h1 <= h2,
+            (AtHeightOrTime { time: t1, .. }, AtTime(t2)) => t1 <= t2,
+            (
+                AtHeightOrTime {
+                    height: h1,
+                    time: t1,
+                },
+                AtHeightOrTime {
+                    height: h2,
+                    time: t2,
+                },
+            ) => h1 <= h2 && t1 <= t2,
+            (
+                AtHeightOrTime {
+                    height: h1,
+                    time: t1,
+                },
+                AtHeightAndTime {
+                    height: h2,
+                    time: t2,
+                },
+            ) => h1 <= h2 || t1 <= t2,
+            (
+                AtHeightAndTime {
+                    height: h1,
+                    time: t1,
+                },
+                AtHeightAndTime {
+                    height: h2,
+                    time: t2,
+                },
+            ) => h1 <= h2 && t1 <= t2,
+            // one can expire by height while the other one needs time to pass, or vice versa
+            _ => false,
+        }
+    }
 }
 
 impl Add<Duration> for Expiration {
@@ -51,10 +204,35 @@ impl Add<Duration> for Expiration {
     fn add(self, duration: Duration) -> StdResult<Expiration> {
         match (self, duration) {
             (Expiration::AtTime(t), Duration::Time(delta)) => {
-                Ok(Expiration::AtTime(t.plus_seconds(delta)))
+                Ok(Expiration::AtTime(checked_plus_seconds(t, delta)?))
             }
             (Expiration::AtHeight(h), Duration::Height(delta)) => {
-                Ok(Expiration::AtHeight(h + delta))
+                Ok(Expiration::AtHeight(checked_plus_height(h, delta)?))
+            }
+            // compound expirations only move the part matching the duration
+            (Expiration::AtHeightOrTime { height, time }, Duration::Height(delta)) => {
+                Ok(Expiration::AtHeightOrTime {
+                    height: checked_plus_height(height, delta)?,
+                    time,
+                })
+            }
+            (Expiration::AtHeightOrTime { height, time }, Duration::Time(delta)) => {
+                Ok(Expiration::AtHeightOrTime {
+                    height,
+                    time: checked_plus_seconds(time, delta)?,
+                })
+            }
+            (Expiration::AtHeightAndTime { height, time }, Duration::Height(delta)) => {
+                Ok(Expiration::AtHeightAndTime {
+                    height: checked_plus_height(height, delta)?,
+                    time,
+                })
+            }
+            (Expiration::AtHeightAndTime { height, time }, Duration::Time(delta)) => {
+                Ok(Expiration::AtHeightAndTime {
+                    height,
+                    time: checked_plus_seconds(time, delta)?,
+                })
             }
             (Expiration::Never {}, _) => Ok(Expiration::Never {}),
             _ => Err(StdError::generic_err("Cannot add height and time")),
@@ -62,26 +240,114 @@ impl Add<Duration> for Expiration {
     }
 }
 
-// TODO: does this make sense? do we get expected info/error when None is returned???
+/// Adds `delta` blocks to `height`, erroring on overflow
+pub(crate) fn checked_plus_height(height: u64, delta: u64) -> StdResult<u64> {
+    Ok(Uint64::new(height).checked_add(Uint64::new(delta))?.u64())
+}
+
+/// Like `Timestamp::plus_seconds`, but errors on overflow rather than panicking
+pub(crate) fn checked_plus_seconds(time: Timestamp, seconds: u64) -> StdResult<Timestamp> {
+    let nanos = Uint64::new(seconds).checked_mul(Uint64::new(1_000_000_000))?;
+    let nanos = Uint64::new(time.nanos()).checked_add(nanos)?;
+    Ok(Timestamp::from_nanos(nanos.u64()))
+}
+
+/// Expirations are ordered by when they expire. One expiration is less than another one
+/// if it is guaranteed to expire no later, no matter how heights and times progress.
+/// Expirations that may expire in either order (e.g. mismatched heights and times)
+/// cannot be compared, use `Expiration::compare_with` to order them by estimation.
 impl PartialOrd for Expiration {
     fn partial_cmp(&self, other: &Expiration) -> Option<Ordering> {
-        match (self, other) {
-            // compare if both height or both time
-            (Expiration::AtHeight(h1), Expiration::AtHeight(h2)) => Some(h1.cmp(h2)),
-            (Expiration::AtTime(t1), Expiration::AtTime(t2)) => Some(t1.cmp(t2)),
-            // if at least one is never, we can compare with anything
-            (Expiration::Never {}, Expiration::Never {}) => Some(Ordering::Equal),
-            (Expiration::Never {}, _) => Some(Ordering::Greater),
-            (_, Expiration::Never {}) => Some(Ordering::Less),
+        if self == other {
+            return Some(Ordering::Equal);
+        }
+        match (
+            self.expires_no_later_than(other),
+            other.expires_no_later_than(self),
+        ) {
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
             // if they are mis-matched finite ends, no compare possible
             _ => None,
         }
     }
 }
 
+/// BlockTimeEstimate relates heights to times, assuming blocks are produced at a
+/// constant rate. This is only an estimation, but allows to compare and convert
+/// height- and time-based expirations deterministically.
+#[cw_serde]
+#[derive(Copy)]
+pub struct BlockTimeEstimate {
+    /// A reference height, usually of a past block
+    pub height: u64,
+    /// The time at the reference height
+    pub time: Timestamp,
+    /// The average number of seconds between two blocks
+    pub seconds_per_block: Decimal,
+}
+
+/// `Decimal` has 18 fractional digits, so its atomics for a number of seconds are
+/// 10^-9 nanoseconds each
+const ATOMICS_PER_NANO: u128 = 1_000_000_000;
+
+impl BlockTimeEstimate {
+    /// Estimates the time of the block at the given height.
+    /// Saturates at the minimal and maximal `Timestamp`.
+    pub fn time_at(&self, height: u64) -> Timestamp {
+        let blocks = height.abs_diff(self.height);
+        let elapsed =
+            self.seconds_per_block.atomics().full_mul(blocks) / Uint256::from(ATOMICS_PER_NANO);
+        let elapsed = Uint128::try_from(elapsed)
+            .ok()
+            .and_then(|nanos| u64::try_from(nanos.u128()).ok())
+            .unwrap_or(u64::MAX);
+        let nanos = if height >= self.height {
+            self.time.nanos().saturating_add(elapsed)
+        } else {
+            self.time.nanos().saturating_sub(elapsed)
+        };
+        Timestamp::from_nanos(nanos)
+    }
+
+    /// Estimates the height of the first block at or after the given time.
+    /// Saturates at height 0 and `u64::MAX`. If `seconds_per_block` is zero, all
+    /// times map to the reference height.
+    pub fn height_at(&self, time: Timestamp) -> u64 {
+        let block_atomics = self.seconds_per_block.atomics().u128();
+        if block_atomics == 0 {
+            return self.height;
+        }
+        let elapsed = time.nanos().abs_diff(self.time.nanos()) as u128 * ATOMICS_PER_NANO;
+        if time >= self.time {
+            let blocks = u64::try_from(elapsed.div_ceil(block_atomics)).unwrap_or(u64::MAX);
+            self.height.saturating_add(blocks)
+        } else {
+            let blocks = u64::try_from(elapsed / block_atomics).unwrap_or(u64::MAX);
+            self.height.saturating_sub(blocks)
+        }
+    }
+}
+
+pub const MINUTE: Duration = Duration::Time(60);
 pub const HOUR: Duration = Duration::Time(60 * 60);
 pub const DAY: Duration = Duration::Time(24 * 60 * 60);
 pub const WEEK: Duration = Duration::Time(7 * 24 * 60 * 60);
+/// A month of 30 days
+pub const MONTH: Duration = Duration::Time(30 * 24 * 60 * 60);
+/// A year of 365 days
+pub const YEAR: Duration = Duration::Time(365 * 24 * 60 * 60);
+
+/// The time units used for parsing and formatting durations, largest first
+const TIME_UNITS: [(&str, Duration); 7] = [
+    ("year", YEAR),
+    ("month", MONTH),
+    ("week", WEEK),
+    ("day", DAY),
+    ("hour", HOUR),
+    ("minute", MINUTE),
+    ("second", Duration::Time(1)),
+];
 
 /// Duration is a delta of time. You can add it to a BlockInfo or Expiration to
 /// move that further in the future. Note that an height-based Duration and
@@ -104,19 +370,99 @@ impl fmt::Display for Duration {
 }
 
 impl Duration {
-    /// Create an expiration for Duration after current block
+    /// Create an expiration for Duration after current block.
+    /// This saturates rather than panicking for huge durations,
+    /// use `checked_after` to detect that.
     pub fn after(&self, block: &BlockInfo) -> Expiration {
         match self {
-            Duration::Height(h) => Expiration::AtHeight(block.height + h),
-            Duration::Time(t) => Expiration::AtTime(block.time.plus_seconds(*t)),
+            Duration::Height(h) => Expiration::AtHeight(block.height.saturating_add(*h)),
+            Duration::Time(t) => Expiration::AtTime(
+                checked_plus_seconds(block.time, *t)
+                    .unwrap_or_else(|_| Timestamp::from_nanos(u64::MAX)),
+            ),
+        }
+    }
+
+    /// Create an expiration for Duration after current block, erroring on overflow
+    pub fn checked_after(&self, block: &BlockInfo) -> StdResult<Expiration> {
+        match self {
+            Duration::Height(h) => Ok(Expiration::AtHeight(checked_plus_height(block.height, *h)?)),
+            Duration::Time(t) => Ok(Expiration::AtTime(checked_plus_seconds(block.time, *t)?)),
+        }
+    }
+
+    /// Returns the block height or the block time in seconds, matching the kind of this duration.
+    /// This is the position of the block on the axis the duration is measured in.
+    pub fn block_position(&self, block: &BlockInfo) -> u64 {
+        match self {
+            Duration::Height(_) => block.height,
+            Duration::Time(_) => block.time.seconds(),
         }
     }
 
     // creates a number just a little bigger, so we can use it to pass expiration point
     pub fn plus_one(&self) -> Duration {
         match self {
-            Duration::Height(h) => Duration::Height(h + 1),
-            Duration::Time(t) => Duration::Time(t + 1),
+            Duration::Height(h) => Duration::Height(h.saturating_add(1)),
+            Duration::Time(t) => Duration::Time(t.saturating_add(1)),
+        }
+    }
+
+    /// Formats the duration for humans, e.g. `"1 week 12 hours"` or `"1000 blocks"`.
+    /// Time is split into years (365 days), months (30 days), weeks, days, hours,
+    /// minutes and seconds. The result can be parsed back with `FromStr`.
+    pub fn to_human_string(&self) -> String {
+        match self {
+            Duration::Height(1) => "1 block".to_string(),
+            Duration::Height(height) => format!("{} blocks", height),
+            Duration::Time(0) => "0 seconds".to_string(),
+            Duration::Time(time) => {
+                let mut rest = *time;
+                let mut parts = vec![];
+                for (name, unit) in TIME_UNITS {
+                    let unit = unit.amount();
+                    match rest / unit {
+                        0 => {}
+                        1 => parts.push(format!("1 {}", name)),
+                        amount => parts.push(format!("{} {}s", amount, name)),
+                    }
+                    rest %= unit;
+                }
+                parts.join(" ")
+            }
+        }
+    }
+
+    /// the number of seconds or blocks in this duration
+    const fn amount(&self) -> u64 {
+        match self {
+            Duration::Height(h) => *h,
+            Duration::Time(t) => *t,
+        }
+    }
+
+    /// Adds two durations of the same kind, erroring on mismatched kinds or overflow
+    pub fn checked_add(self, rhs: Duration) -> StdResult<Duration> {
+        match (self, rhs) {
+            (Duration::Time(t), Duration::Time(t2)) => Ok(Duration::Time(
+                Uint64::new(t).checked_add(Uint64::new(t2))?.u64(),
+            )),
+            (Duration::Height(h), Duration::Height(h2)) => {
+                Ok(Duration::Height(checked_plus_height(h, h2)?))
+            }
+            _ => Err(StdError::generic_err("Cannot add height and time")),
+        }
+    }
+
+    /// Multiplies the duration, erroring on overflow
+    pub fn checked_mul(self, rhs: u64) -> StdResult<Duration> {
+        match self {
+            Duration::Time(t) => Ok(Duration::Time(
+                Uint64::new(t).checked_mul(Uint64::new(rhs))?.u64(),
+            )),
+            Duration::Height(h) => Ok(Duration::Height(
+                Uint64::new(h).checked_mul(Uint64::new(rhs))?.u64(),
+            )),
         }
     }
 }
@@ -125,22 +471,116 @@ impl Add<Duration> for Duration {
     type Output = StdResult<Duration>;
 
     fn add(self, rhs: Duration) -> StdResult<Duration> {
-        match (self, rhs) {
-            (Duration::Time(t), Duration::Time(t2)) => Ok(Duration::Time(t + t2)),
-            (Duration::Height(h), Duration::Height(h2)) => Ok(Duration::Height(h + h2)),
-            _ => Err(StdError::generic_err("Cannot add height and time")),
+        self.checked_add(rhs)
+    }
+}
+
+/// Parses a duration like `"7d12h"`, `"1 week 12 hours"`, `"90m"` or `"1000 blocks"`.
+///
+/// Time durations are one or more `<amount><unit>` pairs, optionally separated by whitespace,
+/// which are summed up. Units are `s`/`sec`/`second`, `m`/`min`/`minute`, `h`/`hour`,
+/// `d`/`day`, `w`/`week`, `mo`/`month` (30 days) and `y`/`year` (365 days), all of which
+/// may also be plural. Height durations are `<amount> block` or `<amount> blocks`.
+/// The `Display` format (`"time: <seconds>"` or `"height: <blocks>"`) is accepted as well.
+impl FromStr for Duration {
+    type Err = StdError;
+
+    fn from_str(input: &str) -> StdResult<Duration> {
+        let input = input.trim();
+        if let Some(height) = input.strip_prefix("height:") {
+            return Ok(Duration::Height(parse_u64(height.trim(), "Duration")?));
+        }
+        if let Some(time) = input.strip_prefix("time:") {
+            return Ok(Duration::Time(parse_u64(time.trim(), "Duration")?));
+        }
+        if input.is_empty() {
+            return Err(StdError::parse_err("Duration", "empty duration"));
         }
+
+        let mut rest = input;
+        let mut total: Option<Duration> = None;
+        while !rest.is_empty() {
+            let amount_len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let amount = parse_u64(&rest[..amount_len], "Duration")?;
+            rest = rest[amount_len..].trim_start();
+            let unit_len = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            let unit = &rest[..unit_len];
+            rest = rest[unit_len..].trim_start();
+
+            let part = match unit {
+                "block" | "blocks" => Duration::Height(amount),
+                _ => time_unit(unit)
+                    .ok_or_else(|| {
+                        StdError::parse_err("Duration", format!("unknown unit '{}'", unit))
+                    })?
+                    .checked_mul(amount)?,
+            };
+            total = Some(match total {
+                Some(total) => total.checked_add(part)?,
+                None => part,
+            });
+        }
+        // the loop ran at least once, as the input is not empty
+        total.ok_or_else(|| StdError::parse_err("Duration", "empty duration"))
+    }
+}
+
+fn time_unit(unit: &str) -> Option<Duration> {
+    match unit {
+        "s" | "sec" | "secs" | "second" | "seconds" => Some(Duration::Time(1)),
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(MINUTE),
+        "h" | "hour" | "hours" => Some(HOUR),
+        "d" | "day" | "days" => Some(DAY),
+        "w" | "week" | "weeks" => Some(WEEK),
+        "mo" | "month" | "months" => Some(MONTH),
+        "y" | "year" | "years" => Some(YEAR),
+        _ => None,
     }
 }
 
+// only ASCII digits, as `str::parse` also accepts a leading '+'
+fn parse_u64(input: &str, target: &str) -> StdResult<u64> {
+    let invalid = || StdError::parse_err(target, format!("invalid number '{}'", input));
+    if input.is_empty() || !input.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    input.parse().map_err(|_| invalid())
+}
+
+/// Parses `<seconds>[.<nanoseconds>]`, the format `Timestamp` is displayed in
+fn parse_timestamp(input: &str) -> StdResult<Timestamp> {
+    let (seconds, nanos) = match input.split_once('.') {
+        Some((seconds, nanos)) => {
+            if nanos.is_empty() || nanos.len() > 9 {
+                return Err(StdError::parse_err(
+                    "Expiration",
+                    format!("invalid time '{}'", input),
+                ));
+            }
+            // right pad the fraction to nanoseconds
+            let nanos = parse_u64(nanos, "Expiration")? * 10u64.pow(9 - nanos.len() as u32);
+            (seconds, nanos)
+        }
+        None => (input, 0),
+    };
+    let time = checked_plus_seconds(Timestamp::from_nanos(0), parse_u64(seconds, "Expiration")?)?;
+    let nanos = Uint64::new(time.nanos()).checked_add(Uint64::new(nanos))?;
+    Ok(Timestamp::from_nanos(nanos.u64()))
+}
+
+/// Saturates rather than panicking on overflow, use `Duration::checked_mul` to detect that
 impl Mul<u64> for Duration {
     type Output = Duration;
 
     fn mul(self, rhs: u64) -> Self::Output {
-        match self {
-            Duration::Time(t) => Duration::Time(t * rhs),
-            Duration::Height(h) => Duration::Height(h * rhs),
-        }
+        self.checked_mul(rhs).unwrap_or(match self {
+            Duration::Time(_) => Duration::Time(u64::MAX),
+            Duration::Height(_) => Duration::Height(u64::MAX),
+        })
     }
 }
 
@@ -185,6 +625,290 @@ mod test {
         assert!(!(Expiration::AtTime(Timestamp::from_seconds(1000)) == Expiration::AtHeight(230)));
     }
 
+    #[test]
+    fn compound_expiration_is_expired() {
+        let block = |height: u64, time: u64| BlockInfo {
+            height,
+            time: Timestamp::from_seconds(time),
+            chain_id: "foo".to_string(),
+        };
+        let or = Expiration::AtHeightOrTime {
+            height: 100,
+            time: Timestamp::from_seconds(1000),
+        };
+        let and = Expiration::AtHeightAndTime {
+            height: 100,
+            time: Timestamp::from_seconds(1000),
+        };
+
+        assert!(!or.is_expired(&block(99, 999)));
+        assert!(or.is_expired(&block(100, 999)));
+        assert!(or.is_expired(&block(99, 1000)));
+        assert!(or.is_expired(&block(100, 1000)));
+
+        assert!(!and.is_expired(&block(99, 999)));
+        assert!(!and.is_expired(&block(100, 999)));
+        assert!(!and.is_expired(&block(99, 1000)));
+        assert!(and.is_expired(&block(100, 1000)));
+    }
+
+    #[test]
+    fn compare_compound_expiration() {
+        let or = |height: u64, time: u64| Expiration::AtHeightOrTime {
+            height,
+            time: Timestamp::from_seconds(time),
+        };
+        let and = |height: u64, time: u64| Expiration::AtHeightAndTime {
+            height,
+            time: Timestamp::from_seconds(time),
+        };
+        let at_time = |time: u64| Expiration::AtTime(Timestamp::from_seconds(time));
+
+        // whichever comes first is never later than each part
+        assert!(or(100, 1000) < Expiration::AtHeight(100));
+        assert!(or(100, 1000) < at_time(1000));
+        assert!(or(100, 1000) < Expiration::AtHeight(200));
+        assert_eq!(or(100, 1000).partial_cmp(&Expiration::AtHeight(99)), None);
+        // whichever comes last is never earlier than each part
+        assert!(and(100, 1000) > Expiration::AtHeight(100));
+        assert!(and(100, 1000) > at_time(1000));
+        assert_eq!(and(100, 1000).partial_cmp(&at_time(1001)), None);
+        // either is less than the other one
+        assert!(or(100, 1000) < and(100, 1000));
+        assert!(or(100, 1000) < and(50, 1000));
+        assert_eq!(or(100, 1000).partial_cmp(&and(50, 500)), None);
+
+        // same kinds compare by both parts
+        assert!(or(100, 1000) < or(101, 1000));
+        assert!(and(100, 1000) > and(100, 999));
+        assert_eq!(
+            or(100, 1000).partial_cmp(&or(100, 1000)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(or(100, 1000).partial_cmp(&or(99, 1001)), None);
+        assert_eq!(and(100, 1000).partial_cmp(&and(101, 999)), None);
+
+        // never as infinity
+        assert!(or(100, 1000) < Expiration::Never {});
+        assert!(Expiration::Never {} > and(100, 1000));
+    }
+
+    #[test]
+    fn ordering_matches_expiry() {
+        let mut expirations = vec![Expiration::Never {}];
+        for height in [10, 20, 30] {
+            expirations.push(Expiration::AtHeight(height));
+            for time in [100, 200, 300] {
+                let time = Timestamp::from_seconds(time);
+                expirations.push(Expiration::AtTime(time));
+                expirations.push(Expiration::AtHeightOrTime { height, time });
+                expirations.push(Expiration::AtHeightAndTime { height, time });
+            }
+        }
+        let mut blocks = vec![];
+        for height in 0..=40 {
+            for time in (0..=400).step_by(10) {
+                blocks.push(BlockInfo {
+                    height,
+                    time: Timestamp::from_seconds(time),
+                    chain_id: "foo".to_string(),
+                });
+            }
+        }
+
+        for a in &expirations {
+            for b in &expirations {
+                // a <= b iff a is expired on every block b is expired on
+                let no_later = blocks
+                    .iter()
+                    .all(|block| !b.is_expired(block) || a.is_expired(block));
+                assert_eq!(a <= b, no_later, "{} <= {}", a, b);
+            }
+        }
+    }
+
+    fn estimate() -> BlockTimeEstimate {
+        // 5.5 seconds per block, height 1000 at 10000s
+        BlockTimeEstimate {
+            height: 1000,
+            time: Timestamp::from_seconds(10000),
+            seconds_per_block: Decimal::permille(5500),
+        }
+    }
+
+    #[test]
+    fn block_time_estimate() {
+        let estimate = estimate();
+        assert_eq!(estimate.time_at(1000), Timestamp::from_seconds(10000));
+        assert_eq!(estimate.time_at(1002), Timestamp::from_seconds(10011));
+        assert_eq!(
+            estimate.time_at(1001),
+            Timestamp::from_nanos(10_005_500_000_000)
+        );
+        assert_eq!(estimate.time_at(998), Timestamp::from_seconds(9989));
+
+        assert_eq!(estimate.height_at(Timestamp::from_seconds(10000)), 1000);
+        assert_eq!(estimate.height_at(Timestamp::from_seconds(10011)), 1002);
+        // rounds up to the first block at or after the time
+        assert_eq!(estimate.height_at(Timestamp::from_seconds(10001)), 1001);
+        assert_eq!(estimate.height_at(Timestamp::from_seconds(10006)), 1002);
+        // also in the past
+        assert_eq!(estimate.height_at(Timestamp::from_seconds(9989)), 998);
+        assert_eq!(estimate.height_at(Timestamp::from_seconds(9990)), 999);
+
+        // round trip
+        for height in [0, 1, 999, 1000, 1001, 123456789, 3_000_000_000] {
+            assert_eq!(estimate.height_at(estimate.time_at(height)), height);
+        }
+
+        // saturates
+        assert_eq!(estimate.time_at(0), Timestamp::from_seconds(4500));
+        assert_eq!(estimate.time_at(u64::MAX), Timestamp::from_nanos(u64::MAX));
+        assert_eq!(estimate.height_at(Timestamp::from_seconds(0)), 0);
+        let estimate = BlockTimeEstimate {
+            seconds_per_block: Decimal::raw(1),
+            ..estimate
+        };
+        assert_eq!(
+            estimate.height_at(Timestamp::from_nanos(u64::MAX)),
+            u64::MAX
+        );
+        let estimate = BlockTimeEstimate {
+            seconds_per_block: Decimal::zero(),
+            ..estimate
+        };
+        assert_eq!(estimate.height_at(Timestamp::from_seconds(20000)), 1000);
+    }
+
+    #[test]
+    fn convert_expiration() {
+        let estimate = estimate();
+        let time = Timestamp::from_seconds(10011);
+
+        assert_eq!(
+            Expiration::AtHeight(1002).to_time(&estimate),
+            Expiration::AtTime(time)
+        );
+        assert_eq!(
+            Expiration::AtTime(time).to_time(&estimate),
+            Expiration::AtTime(time)
+        );
+        assert_eq!(
+            Expiration::AtTime(time).to_height(&estimate),
+            Expiration::AtHeight(1002)
+        );
+        assert_eq!(
+            Expiration::AtHeight(1002).to_height(&estimate),
+            Expiration::AtHeight(1002)
+        );
+        assert_eq!(
+            Expiration::Never {}.to_time(&estimate),
+            Expiration::Never {}
+        );
+        assert_eq!(
+            Expiration::Never {}.to_height(&estimate),
+            Expiration::Never {}
+        );
+
+        // whichever comes first
+        let or = Expiration::AtHeightOrTime { height: 1010, time };
+        assert_eq!(or.to_time(&estimate), Expiration::AtTime(time));
+        assert_eq!(or.to_height(&estimate), Expiration::AtHeight(1002));
+        // whichever comes last
+        let and = Expiration::AtHeightAndTime { height: 1010, time };
+        assert_eq!(
+            and.to_time(&estimate),
+            Expiration::AtTime(Timestamp::from_seconds(10055))
+        );
+        assert_eq!(and.to_height(&estimate), Expiration::AtHeight(1010));
+    }
+
+    #[test]
+    fn compare_with_estimate() {
+        let estimate = estimate();
+
+        // height 1002 is estimated at 10011s
+        let height = Expiration::AtHeight(1002);
+        let before = Expiration::AtTime(Timestamp::from_seconds(10010));
+        let after = Expiration::AtTime(Timestamp::from_seconds(10012));
+        assert_eq!(height.partial_cmp(&before), None);
+        assert_eq!(height.compare_with(&before, &estimate), Ordering::Greater);
+        assert_eq!(height.compare_with(&after, &estimate), Ordering::Less);
+        assert_eq!(before.compare_with(&height, &estimate), Ordering::Less);
+        assert_eq!(
+            height.compare_with(&Expiration::Never {}, &estimate),
+            Ordering::Less
+        );
+        assert_eq!(
+            Expiration::Never {}.compare_with(&Expiration::Never {}, &estimate),
+            Ordering::Equal
+        );
+
+        // mixed expirations can be sorted
+        let mut expirations = vec![
+            Expiration::Never {},
+            after,
+            Expiration::AtHeight(1003),
+            height,
+            before,
+            Expiration::AtHeightAndTime {
+                height: 1000,
+                time: Timestamp::from_nanos(10_011_500_000_000),
+            },
+        ];
+        expirations.sort_by(|a, b| a.compare_with(b, &estimate));
+        assert_eq!(
+            expirations,
+            vec![
+                before,
+                height,
+                Expiration::AtHeightAndTime {
+                    height: 1000,
+                    time: Timestamp::from_nanos(10_011_500_000_000),
+                },
+                after,
+                Expiration::AtHeight(1003),
+                Expiration::Never {},
+            ]
+        );
+
+        // ties where partial_cmp can tell them apart: height 1002 is after 10000s anyway
+        let and = Expiration::AtHeightAndTime {
+            height: 1002,
+            time: Timestamp::from_seconds(10000),
+        };
+        assert_eq!(height.partial_cmp(&and), Some(Ordering::Less));
+        assert_eq!(height.compare_with(&and, &estimate), Ordering::Equal);
+
+        // never contradicts partial_cmp
+        let mut all = vec![Expiration::Never {}];
+        for height in [995, 1000, 1003] {
+            all.push(Expiration::AtHeight(height));
+            for time in [9900, 10000, 10020] {
+                let time = Timestamp::from_seconds(time);
+                all.push(Expiration::AtTime(time));
+                all.push(Expiration::AtHeightOrTime { height, time });
+                all.push(Expiration::AtHeightAndTime { height, time });
+            }
+        }
+        for a in &all {
+            for b in &all {
+                match a.partial_cmp(b) {
+                    Some(Ordering::Less) => {
+                        assert_ne!(a.compare_with(b, &estimate), Ordering::Greater)
+                    }
+                    Some(Ordering::Greater) => {
+                        assert_ne!(a.compare_with(b, &estimate), Ordering::Less)
+                    }
+                    Some(Ordering::Equal) => {
+                        assert_eq!(a.compare_with(b, &estimate), Ordering::Equal)
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+
     #[test]
     fn expiration_addition() {
         // height
@@ -206,11 +930,49 @@ mod test {
         let end = Expiration::AtHeight(12345) + Duration::Time(1500);
         end.unwrap_err();
 
+        // compound only moves the matching part
+        let time = Timestamp::from_seconds(55544433);
+        let end = Expiration::AtHeightOrTime {
+            height: 12345,
+            time,
+        } + Duration::Height(400);
+        assert_eq!(
+            end.unwrap(),
+            Expiration::AtHeightOrTime {
+                height: 12745,
+                time
+            }
+        );
+        let end = Expiration::AtHeightAndTime {
+            height: 12345,
+            time,
+        } + Duration::Time(40300);
+        assert_eq!(
+            end.unwrap(),
+            Expiration::AtHeightAndTime {
+                height: 12345,
+                time: Timestamp::from_seconds(55584733)
+            }
+        );
+
         // // not possible other way
         // let end = Duration::Time(1000) + Expiration::AtTime(50000);
         // assert_eq!(end.unwrap(), Expiration::AtTime(51000));
     }
 
+    #[test]
+    fn expiration_display() {
+        let time = Timestamp::from_seconds(1000);
+        assert_eq!(
+            Expiration::AtHeightOrTime { height: 100, time }.to_string(),
+            "expiration height: 100 or time: 1000.000000000"
+        );
+        assert_eq!(
+            Expiration::AtHeightAndTime { height: 100, time }.to_string(),
+            "expiration height: 100 and time: 1000.000000000"
+        );
+    }
+
     #[test]
     fn block_plus_duration() {
         let block = BlockInfo {
@@ -234,4 +996,266 @@ mod test {
         let days = DAY * 3;
         assert_eq!(Duration::Time(3 * 24 * 60 * 60), days);
     }
+
+    #[test]
+    fn parse_duration() {
+        for (input, expected) in [
+            ("7d12h", Duration::Time(7 * 86400 + 12 * 3600)),
+            ("90m", Duration::Time(90 * 60)),
+            ("1000 blocks", Duration::Height(1000)),
+            ("1 block", Duration::Height(1)),
+            ("1block", Duration::Height(1)),
+            ("30s", Duration::Time(30)),
+            ("1 week 12 hours", Duration::Time(7 * 86400 + 12 * 3600)),
+            (
+                "2w 1d 3h 4m 5s",
+                Duration::Time(15 * 86400 + 3 * 3600 + 4 * 60 + 5),
+            ),
+            ("1mo", MONTH),
+            ("2 months", MONTH * 2),
+            ("1y", YEAR),
+            ("3 years", YEAR * 3),
+            (" 1 minute ", MINUTE),
+            ("0s", Duration::Time(0)),
+            // units may repeat
+            ("1h 1h", HOUR * 2),
+            // display format
+            ("time: 86400", DAY),
+            ("height: 12", Duration::Height(12)),
+        ] {
+            assert_eq!(input.parse::<Duration>().unwrap(), expected, "{}", input);
+        }
+
+        for input in [
+            "",
+            "   ",
+            "12",
+            "d",
+            "1x",
+            "1 block 1d",
+            "1.5h",
+            "-1s",
+            "1d -1s",
+            "time: x",
+            "+5s",
+            "time: +5",
+            "height:+5",
+            "99999999999999999999s",
+        ] {
+            input.parse::<Duration>().unwrap_err();
+        }
+
+        let err = "18446744073709551615y".parse::<Duration>().unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+    }
+
+    #[test]
+    fn human_duration() {
+        assert_eq!(
+            Duration::Time(7 * 86400 + 12 * 3600).to_human_string(),
+            "1 week 12 hours"
+        );
+        assert_eq!(Duration::Time(0).to_human_string(), "0 seconds");
+        assert_eq!(Duration::Time(1).to_human_string(), "1 second");
+        assert_eq!(
+            Duration::Time(90 * 60).to_human_string(),
+            "1 hour 30 minutes"
+        );
+        assert_eq!(
+            Duration::Time(365 * 86400 + 2 * 30 * 86400 + 86400 + 61).to_human_string(),
+            "1 year 2 months 1 day 1 minute 1 second"
+        );
+        assert_eq!(Duration::Height(1).to_human_string(), "1 block");
+        assert_eq!(Duration::Height(1000).to_human_string(), "1000 blocks");
+        assert_eq!(Duration::Height(0).to_human_string(), "0 blocks");
+
+        // round trips
+        for duration in [
+            Duration::Time(0),
+            Duration::Time(1),
+            Duration::Time(59),
+            Duration::Time(3601),
+            Duration::Time(123456789),
+            Duration::Time(u64::MAX),
+            DAY * 400,
+            Duration::Height(0),
+            Duration::Height(1),
+            Duration::Height(u64::MAX),
+        ] {
+            let human = duration.to_human_string();
+            assert_eq!(human.parse::<Duration>().unwrap(), duration, "{}", human);
+            let display = duration.to_string();
+            assert_eq!(
+                display.parse::<Duration>().unwrap(),
+                duration,
+                "{}",
+                display
+            );
+        }
+    }
+
+    #[test]
+    fn parse_expiration() {
+        let time = Timestamp::from_seconds(1650000000);
+        for (input, expected) in [
+            ("never", Expiration::Never {}),
+            ("height 12345", Expiration::AtHeight(12345)),
+            ("time 1650000000", Expiration::AtTime(time)),
+            (
+                "time 1650000000.5",
+                Expiration::AtTime(time.plus_nanos(500_000_000)),
+            ),
+            (
+                "time 1650000000.000000001",
+                Expiration::AtTime(time.plus_nanos(1)),
+            ),
+            (
+                "height 12345 or time 1650000000",
+                Expiration::AtHeightOrTime {
+                    height: 12345,
+                    time,
+                },
+            ),
+            (
+                "height 12345 and time 1650000000",
+                Expiration::AtHeightAndTime {
+                    height: 12345,
+                    time,
+                },
+            ),
+            ("expiration height: 12345", Expiration::AtHeight(12345)),
+            ("expiration: never", Expiration::Never {}),
+        ] {
+            assert_eq!(input.parse::<Expiration>().unwrap(), expected, "{}", input);
+        }
+
+        for input in [
+            "",
+            "soon",
+            "height",
+            "height x",
+            "height 1 2",
+            "height +1",
+            "time +1",
+            "time 1.+5",
+            "time 1.",
+            "time 1.0000000001",
+            "time 18446744073709551615",
+            "height 1 xor time 2",
+            "time 1 or height 2",
+        ] {
+            input.parse::<Expiration>().unwrap_err();
+        }
+
+        // display round trips
+        for expiration in [
+            Expiration::Never {},
+            Expiration::AtHeight(0),
+            Expiration::AtHeight(u64::MAX),
+            Expiration::AtTime(time.plus_nanos(123)),
+            Expiration::AtHeightOrTime { height: 5, time },
+            Expiration::AtHeightAndTime { height: 5, time },
+        ] {
+            let display = expiration.to_string();
+            assert_eq!(
+                display.parse::<Expiration>().unwrap(),
+                expiration,
+                "{}",
+                display
+            );
+        }
+    }
+
+    #[test]
+    fn checked_duration_math() {
+        let long = Duration::Time(444)
+            .checked_add(Duration::Time(555))
+            .unwrap();
+        assert_eq!(Duration::Time(999), long);
+        Duration::Time(444)
+            .checked_add(Duration::Height(555))
+            .unwrap_err();
+        let err = Duration::Height(u64::MAX)
+            .checked_add(Duration::Height(1))
+            .unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        let err = (Duration::Time(u64::MAX) + Duration::Time(1)).unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+
+        assert_eq!(DAY.checked_mul(3).unwrap(), DAY * 3);
+        let err = Duration::Height(u64::MAX / 2 + 1)
+            .checked_mul(2)
+            .unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        // the operator saturates
+        assert_eq!(Duration::Height(u64::MAX) * 2, Duration::Height(u64::MAX));
+        assert_eq!(WEEK * u64::MAX, Duration::Time(u64::MAX));
+
+        assert_eq!(Duration::Height(1).plus_one(), Duration::Height(2));
+        assert_eq!(
+            Duration::Height(u64::MAX).plus_one(),
+            Duration::Height(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn checked_block_plus_duration() {
+        let block = BlockInfo {
+            height: 1000,
+            time: Timestamp::from_seconds(7777),
+            chain_id: "foo".to_string(),
+        };
+
+        let end = Duration::Height(456).checked_after(&block).unwrap();
+        assert_eq!(Expiration::AtHeight(1456), end);
+        let end = Duration::Time(1212).checked_after(&block).unwrap();
+        assert_eq!(Expiration::AtTime(Timestamp::from_seconds(8989)), end);
+
+        let err = Duration::Height(u64::MAX)
+            .checked_after(&block)
+            .unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        let err = Duration::Time(u64::MAX).checked_after(&block).unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        // the duration alone still fits into u64 nanoseconds, but not added to the block time
+        let err = Duration::Time(u64::MAX / 1_000_000_000)
+            .checked_after(&block)
+            .unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+
+        // after saturates
+        assert_eq!(
+            Duration::Height(u64::MAX).after(&block),
+            Expiration::AtHeight(u64::MAX)
+        );
+        assert_eq!(
+            Duration::Time(u64::MAX).after(&block),
+            Expiration::AtTime(Timestamp::from_nanos(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn expiration_addition_overflow() {
+        let err = (Expiration::AtHeight(1) + Duration::Height(u64::MAX)).unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        let err = (Expiration::AtTime(Timestamp::from_seconds(1)) + Duration::Time(u64::MAX))
+            .unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        let err = (Expiration::AtHeightOrTime {
+            height: 1,
+            time: Timestamp::from_seconds(1),
+        } + Duration::Height(u64::MAX))
+        .unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        let err = (Expiration::AtHeightAndTime {
+            height: 1,
+            time: Timestamp::from_seconds(1),
+        } + Duration::Time(u64::MAX))
+        .unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+
+        // never stays never
+        let end = Expiration::Never {} + Duration::Height(u64::MAX);
+        assert_eq!(end.unwrap(), Expiration::Never {});
+    }
 }
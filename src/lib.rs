@@ -6,29 +6,56 @@ Types should only be added here after they are duplicated in
 a second contract, not "because we might need it"
 */
 
+mod asset;
 mod balance;
+mod calendar;
+mod curve;
 mod event;
 mod expiration;
 mod migrate;
 mod pagination;
 mod parse_reply;
 mod payment;
+mod recurring;
 mod scheduled;
 mod threshold;
+mod vesting;
 
 pub use migrate::ensure_from_older_version;
 pub use pagination::{
     calc_range_end, calc_range_start, calc_range_start_string, maybe_addr, maybe_canonical,
 };
 pub use parse_reply::{
-    parse_execute_response_data, parse_instantiate_response_data, parse_reply_execute_data,
-    parse_reply_instantiate_data, MsgExecuteContractResponse, MsgInstantiateContractResponse,
-    ParseReplyError,
+    parse_coin_data, parse_create_denom_response_data, parse_delegate_response_data,
+    parse_execute_response_data, parse_instantiate2_response_data, parse_instantiate_response_data,
+    parse_migrate_response_data, parse_reply_create_denom_data, parse_reply_delegate_data,
+    parse_reply_execute_data, parse_reply_instantiate2_data, parse_reply_instantiate_data,
+    parse_reply_migrate_data, parse_reply_store_code_data, parse_reply_transfer_data,
+    parse_reply_undelegate_data, parse_store_code_response_data, parse_timestamp_data,
+    parse_transfer_response_data, parse_undelegate_response_data, MsgCreateDenomResponse,
+    MsgDelegateResponse, MsgExecuteContractResponse, MsgInstantiateContract2Response,
+    MsgInstantiateContractResponse, MsgMigrateContractResponse, MsgStoreCodeResponse,
+    MsgTransferResponse, MsgUndelegateResponse, ParseReplyError, ProtoField, ProtoReader,
+    ProtoValue, ProtoWriter,
+};
+pub use payment::{
+    may_pay, may_pay_many, must_pay, must_pay_asset, must_pay_at_least, must_pay_exact,
+    must_pay_exactly, must_pay_many, nonpayable, one_asset, one_coin, pay_with_change,
+    AssetPayment, PaymentError, PaymentPolicy,
+};
+pub use threshold::{
+    Threshold, ThresholdError, ThresholdResponse, ThresholdTier, ThresholdTierResponse,
+    ThresholdValidationConfig, Vote, VoteStatus, Votes,
 };
-pub use payment::{may_pay, must_pay, nonpayable, one_coin, PaymentError};
-pub use threshold::{Threshold, ThresholdError, ThresholdResponse};
 
-pub use crate::balance::NativeBalance;
+pub use crate::asset::{Asset, AssetInfo, Cw20Amount};
+pub use crate::balance::{GenericBalance, NativeBalance, SplitRemainder};
+pub use crate::calendar::CalendarSchedule;
+pub use crate::curve::{Curve, CurveError};
 pub use crate::event::Event;
-pub use crate::expiration::{Duration, Expiration, DAY, HOUR, WEEK};
+pub use crate::expiration::{
+    BlockTimeEstimate, Duration, Expiration, DAY, HOUR, MINUTE, MONTH, WEEK, YEAR,
+};
+pub use crate::recurring::RecurringSchedule;
 pub use crate::scheduled::Scheduled;
+pub use crate::vesting::VestingSchedule;
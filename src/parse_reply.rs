@@ -1,11 +1,18 @@
+use std::ops::Range;
+
 use thiserror::Error;
 
-use cosmwasm_std::{Binary, Reply};
+use cosmwasm_std::{Binary, Coin, Reply, Timestamp, Uint128};
 
 // Protobuf wire types (https://developers.google.com/protocol-buffers/docs/encoding)
+const WIRE_TYPE_VARINT: u8 = 0;
+const WIRE_TYPE_FIXED64: u8 = 1;
 const WIRE_TYPE_LENGTH_DELIMITED: u8 = 2;
-// Up to 9 bytes of varints as a practical limit (https://github.com/multiformats/unsigned-varint#practical-maximum-of-9-bytes-for-security)
-const VARINT_MAX_BYTES: usize = 9;
+const WIRE_TYPE_FIXED32: u8 = 5;
+// A varint holds up to 64 bits, in groups of 7 bits per byte
+const VARINT_MAX_BYTES: usize = 10;
+// Field numbers are limited to 29 bits
+const MAX_FIELD_NUMBER: u32 = (1 << 29) - 1;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MsgInstantiateContractResponse {
@@ -18,138 +25,757 @@ pub struct MsgExecuteContractResponse {
     pub data: Option<Binary>,
 }
 
-/// Base128 varint decoding.
-/// The remaining of the data is kept in the data parameter.
-fn parse_protobuf_varint(data: &mut Vec<u8>, field_number: u8) -> Result<usize, ParseReplyError> {
-    let data_len = data.len();
-    let mut len: u64 = 0;
-    let mut i = 0;
-    while i < VARINT_MAX_BYTES {
-        if data_len == i {
-            return Err(ParseReplyError::ParseFailure(format!(
-                "failed to decode Protobuf message: field #{}: varint data too short",
+/// instantiate2 responds with the same fields as instantiate
+pub type MsgInstantiateContract2Response = MsgInstantiateContractResponse;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgMigrateContractResponse {
+    pub data: Option<Binary>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgStoreCodeResponse {
+    pub code_id: u64,
+    pub checksum: Binary,
+}
+
+/// Response of an IBC `MsgTransfer`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgTransferResponse {
+    /// sequence number of the transfer packet
+    pub sequence: u64,
+}
+
+/// Response of a staking `MsgDelegate`, which has no fields
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MsgDelegateResponse {}
+
+/// Response of a staking `MsgUndelegate`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgUndelegateResponse {
+    pub completion_time: Timestamp,
+    /// the amount undelegated, only set since Cosmos SDK 0.50
+    pub amount: Option<Coin>,
+}
+
+/// Response of a token factory `MsgCreateDenom`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgCreateDenomResponse {
+    pub new_token_denom: String,
+}
+
+impl MsgInstantiateContractResponse {
+    /// Encodes the response as protobuf, as expected by `parse_instantiate_response_data`.
+    /// Like proto3, empty data is left out, so `Some` empty data is parsed back as `None`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = ProtoWriter::new();
+        writer.write_string(1, &self.contract_address);
+        if let Some(data) = &self.data {
+            writer.write_length_delimited(2, data);
+        }
+        writer.into_vec()
+    }
+
+    /// The encoded response, e.g. to be set as the data of a `Response`
+    pub fn to_binary_proto(&self) -> Binary {
+        Binary(self.encode())
+    }
+}
+
+impl MsgExecuteContractResponse {
+    /// Encodes the response as protobuf, as expected by `parse_execute_response_data`.
+    /// Like proto3, empty data is left out, so `Some` empty data is parsed back as `None`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = ProtoWriter::new();
+        if let Some(data) = &self.data {
+            writer.write_length_delimited(1, data);
+        }
+        writer.into_vec()
+    }
+
+    /// The encoded response, e.g. to be set as the data of a `Response`
+    pub fn to_binary_proto(&self) -> Binary {
+        Binary(self.encode())
+    }
+}
+
+impl MsgMigrateContractResponse {
+    /// Encodes the response as protobuf, as expected by `parse_migrate_response_data`.
+    /// Like proto3, empty data is left out, so `Some` empty data is parsed back as `None`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = ProtoWriter::new();
+        if let Some(data) = &self.data {
+            writer.write_length_delimited(1, data);
+        }
+        writer.into_vec()
+    }
+
+    /// The encoded response, e.g. to be set as the data of a `Response`
+    pub fn to_binary_proto(&self) -> Binary {
+        Binary(self.encode())
+    }
+}
+
+impl MsgStoreCodeResponse {
+    /// Encodes the response as protobuf, as expected by `parse_store_code_response_data`
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = ProtoWriter::new();
+        writer
+            .write_varint(1, self.code_id)
+            .write_length_delimited(2, &self.checksum);
+        writer.into_vec()
+    }
+
+    /// The encoded response, e.g. to be set as the data of a `Response`
+    pub fn to_binary_proto(&self) -> Binary {
+        Binary(self.encode())
+    }
+}
+
+impl MsgTransferResponse {
+    /// Encodes the response as protobuf, as expected by `parse_transfer_response_data`
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = ProtoWriter::new();
+        writer.write_varint(1, self.sequence);
+        writer.into_vec()
+    }
+
+    /// The encoded response, e.g. to be set as the data of a `Response`
+    pub fn to_binary_proto(&self) -> Binary {
+        Binary(self.encode())
+    }
+}
+
+impl MsgDelegateResponse {
+    /// Encodes the response as protobuf, as expected by `parse_delegate_response_data`
+    pub fn encode(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    /// The encoded response, e.g. to be set as the data of a `Response`
+    pub fn to_binary_proto(&self) -> Binary {
+        Binary(self.encode())
+    }
+}
+
+impl MsgUndelegateResponse {
+    /// Encodes the response as protobuf, as expected by `parse_undelegate_response_data`
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = ProtoWriter::new();
+        writer.write_message(1, &encode_timestamp(&self.completion_time));
+        if let Some(amount) = &self.amount {
+            writer.write_message(2, &encode_coin(amount));
+        }
+        writer.into_vec()
+    }
+
+    /// The encoded response, e.g. to be set as the data of a `Response`
+    pub fn to_binary_proto(&self) -> Binary {
+        Binary(self.encode())
+    }
+}
+
+impl MsgCreateDenomResponse {
+    /// Encodes the response as protobuf, as expected by `parse_create_denom_response_data`
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = ProtoWriter::new();
+        writer.write_string(1, &self.new_token_denom);
+        writer.into_vec()
+    }
+
+    /// The encoded response, e.g. to be set as the data of a `Response`
+    pub fn to_binary_proto(&self) -> Binary {
+        Binary(self.encode())
+    }
+}
+
+/// Encodes a `google.protobuf.Timestamp`
+fn encode_timestamp(timestamp: &Timestamp) -> Vec<u8> {
+    let mut writer = ProtoWriter::new();
+    writer
+        .write_varint(1, timestamp.seconds())
+        .write_varint(2, timestamp.subsec_nanos());
+    writer.into_vec()
+}
+
+/// Encodes a `cosmos.base.v1beta1.Coin`
+fn encode_coin(coin: &Coin) -> Vec<u8> {
+    let mut writer = ProtoWriter::new();
+    writer
+        .write_string(1, &coin.denom)
+        .write_string(2, &coin.amount.to_string());
+    writer.into_vec()
+}
+
+/// The value of a single protobuf field, borrowed from the encoded message
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProtoValue<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    LengthDelimited(&'a [u8]),
+    Fixed32(u32),
+}
+
+impl<'a> ProtoValue<'a> {
+    pub fn wire_type(&self) -> u8 {
+        match self {
+            ProtoValue::Varint(_) => WIRE_TYPE_VARINT,
+            ProtoValue::Fixed64(_) => WIRE_TYPE_FIXED64,
+            ProtoValue::LengthDelimited(_) => WIRE_TYPE_LENGTH_DELIMITED,
+            ProtoValue::Fixed32(_) => WIRE_TYPE_FIXED32,
+        }
+    }
+
+    /// Returns the content of a length-delimited field (strings, bytes, messages)
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match self {
+            ProtoValue::LengthDelimited(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a varint field (integers, bools, enums)
+    pub fn as_varint(&self) -> Option<u64> {
+        match self {
+            ProtoValue::Varint(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// A decoded field: `(field_number, wire_type, value)`
+pub type ProtoField<'a> = (u32, u8, ProtoValue<'a>);
+
+/// Minimal protobuf reader, iterating over the `(field_number, wire_type, value)` of
+/// every field in the encoded message without copying it.
+/// Fields are returned in the order they were encoded, so callers decide how to handle
+/// unknown, missing or repeated fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtoReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ProtoReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        ProtoReader { data }
+    }
+
+    /// The data not read yet
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Reads the next field, or None at the end of the message
+    pub fn read_field(&mut self) -> Result<Option<ProtoField<'a>>, ParseReplyError> {
+        if self.data.is_empty() {
+            return Ok(None);
+        }
+        let tag = self.read_varint(0)?;
+        let field_number = match u32::try_from(tag >> 3) {
+            Ok(field_number) if (1..=MAX_FIELD_NUMBER).contains(&field_number) => field_number,
+            _ => return Err(decode_failure(format!("invalid field number {}", tag >> 3))),
+        };
+        let wire_type = (tag & 0b111) as u8;
+        let value = match wire_type {
+            WIRE_TYPE_VARINT => ProtoValue::Varint(self.read_varint(field_number)?),
+            WIRE_TYPE_FIXED64 => {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(self.read_bytes(8, field_number)?);
+                ProtoValue::Fixed64(u64::from_le_bytes(bytes))
+            }
+            WIRE_TYPE_LENGTH_DELIMITED => {
+                let len = self.read_varint(field_number)?;
+                // Gently fall back to the arch's max addressable size
+                let len = usize::try_from(len).unwrap_or(usize::MAX);
+                ProtoValue::LengthDelimited(self.read_bytes(len, field_number)?)
+            }
+            WIRE_TYPE_FIXED32 => {
+                let mut bytes = [0u8; 4];
+                bytes.copy_from_slice(self.read_bytes(4, field_number)?);
+                ProtoValue::Fixed32(u32::from_le_bytes(bytes))
+            }
+            _ => {
+                return Err(decode_failure(format!(
+                    "field #{}: invalid wire type {}",
+                    field_number, wire_type
+                )))
+            }
+        };
+        Ok(Some((field_number, wire_type, value)))
+    }
+
+    /// Base128 varint decoding
+    fn read_varint(&mut self, field_number: u32) -> Result<u64, ParseReplyError> {
+        let mut value: u64 = 0;
+        for (i, byte) in self.data.iter().enumerate().take(VARINT_MAX_BYTES) {
+            value |= ((byte & 0x7f) as u64) << (i * 7);
+            if byte & 0x80 == 0 {
+                // the last byte only has room for a single bit
+                if i == VARINT_MAX_BYTES - 1 && *byte > 1 {
+                    break;
+                }
+                self.data = &self.data[i + 1..];
+                return Ok(value);
+            }
+        }
+        let reason = if self.data.len() < VARINT_MAX_BYTES {
+            "varint data too short"
+        } else {
+            "varint data too long"
+        };
+        Err(decode_failure(format!(
+            "field #{}: {}",
+            field_number, reason
+        )))
+    }
+
+    fn read_bytes(&mut self, len: usize, field_number: u32) -> Result<&'a [u8], ParseReplyError> {
+        if self.data.len() < len {
+            return Err(decode_failure(format!(
+                "field #{}: message too short",
                 field_number
             )));
         }
-        len += ((data[i] & 0x7f) as u64) << (i * 7);
-        if data[i] & 0x80 == 0 {
-            break;
-        }
-        i += 1;
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
     }
-    if i == VARINT_MAX_BYTES {
-        return Err(ParseReplyError::ParseFailure(format!(
-            "failed to decode Protobuf message: field #{}: varint data too long",
-            field_number
-        )));
+}
+
+impl<'a> Iterator for ProtoReader<'a> {
+    type Item = Result<ProtoField<'a>, ParseReplyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = self.read_field();
+        if res.is_err() {
+            // stop after the first error
+            self.data = &[];
+        }
+        res.transpose()
     }
-    *data = data[i + 1..].to_owned();
+}
 
-    Ok(len as usize) // Gently fall back to the arch's max addressable size
+/// Minimal protobuf writer, the counterpart of `ProtoReader`.
+/// Like proto3, empty strings and bytes and zero varints are not written.
+///
+/// All `write_*` methods panic if the field number is 0 or above 2^29 - 1,
+/// as such fields cannot be read back.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProtoWriter {
+    data: Vec<u8>,
 }
 
-/// Helper function to parse length-prefixed protobuf fields.
-/// The remaining of the data is kept in the data parameter.
-fn parse_protobuf_length_prefixed(
-    data: &mut Vec<u8>,
-    field_number: u8,
-) -> Result<Vec<u8>, ParseReplyError> {
-    if data.is_empty() {
-        return Ok(vec![]);
-    };
-    let mut rest_1 = data.split_off(1);
-    let wire_type = data[0] & 0b11;
-    let field = data[0] >> 3;
-
-    if field != field_number {
-        return Err(ParseReplyError::ParseFailure(format!(
-            "failed to decode Protobuf message: invalid field #{} for field #{}",
-            field, field_number
-        )));
+impl ProtoWriter {
+    pub fn new() -> Self {
+        ProtoWriter::default()
     }
-    if wire_type != WIRE_TYPE_LENGTH_DELIMITED {
-        return Err(ParseReplyError::ParseFailure(format!(
-            "failed to decode Protobuf message: field #{}: invalid wire type {}",
-            field_number, wire_type
-        )));
+
+    pub fn write_varint(&mut self, field_number: u32, value: u64) -> &mut Self {
+        check_field_number(field_number);
+        if value != 0 {
+            self.write_tag(field_number, WIRE_TYPE_VARINT);
+            self.write_raw_varint(value);
+        }
+        self
     }
 
-    let len = parse_protobuf_varint(&mut rest_1, field_number)?;
-    if rest_1.len() < len {
-        return Err(ParseReplyError::ParseFailure(format!(
-            "failed to decode Protobuf message: field #{}: message too short",
-            field_number
-        )));
+    pub fn write_length_delimited(&mut self, field_number: u32, value: &[u8]) -> &mut Self {
+        check_field_number(field_number);
+        if !value.is_empty() {
+            self.write_tag(field_number, WIRE_TYPE_LENGTH_DELIMITED);
+            self.write_raw_varint(value.len() as u64);
+            self.data.extend_from_slice(value);
+        }
+        self
+    }
+
+    /// Writes an embedded message, which unlike other fields is written even when empty
+    pub fn write_message(&mut self, field_number: u32, message: &[u8]) -> &mut Self {
+        check_field_number(field_number);
+        self.write_tag(field_number, WIRE_TYPE_LENGTH_DELIMITED);
+        self.write_raw_varint(message.len() as u64);
+        self.data.extend_from_slice(message);
+        self
+    }
+
+    pub fn write_string(&mut self, field_number: u32, value: &str) -> &mut Self {
+        self.write_length_delimited(field_number, value.as_bytes())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        self.data
+    }
+
+    // the public write methods have checked the field number already
+    fn write_tag(&mut self, field_number: u32, wire_type: u8) {
+        self.write_raw_varint(((field_number as u64) << 3) | wire_type as u64);
     }
-    *data = rest_1.split_off(len);
 
-    Ok(rest_1)
+    /// Base128 varint encoding
+    fn write_raw_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.data.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.data.push(value as u8);
+    }
+}
+
+fn check_field_number(field_number: u32) {
+    assert!(
+        (1..=MAX_FIELD_NUMBER).contains(&field_number),
+        "invalid protobuf field number {}",
+        field_number
+    );
 }
 
-fn parse_protobuf_string(data: &mut Vec<u8>, field_number: u8) -> Result<String, ParseReplyError> {
-    let str_field = parse_protobuf_length_prefixed(data, field_number)?;
-    Ok(String::from_utf8(str_field)?)
+fn decode_failure(reason: String) -> ParseReplyError {
+    ParseReplyError::ParseFailure(format!("failed to decode Protobuf message: {}", reason))
+}
+
+fn length_delimited<'a>(
+    value: ProtoValue<'a>,
+    field_number: u32,
+) -> Result<&'a [u8], ParseReplyError> {
+    value.as_bytes().ok_or_else(|| {
+        decode_failure(format!(
+            "field #{}: invalid wire type {}",
+            field_number,
+            value.wire_type()
+        ))
+    })
 }
 
-fn parse_protobuf_bytes(
-    data: &mut Vec<u8>,
-    field_number: u8,
-) -> Result<Option<Binary>, ParseReplyError> {
-    let bytes_field = parse_protobuf_length_prefixed(data, field_number)?;
+fn varint(value: ProtoValue, field_number: u32) -> Result<u64, ParseReplyError> {
+    value.as_varint().ok_or_else(|| {
+        decode_failure(format!(
+            "field #{}: invalid wire type {}",
+            field_number,
+            value.wire_type()
+        ))
+    })
+}
+
+fn parse_protobuf_string(str_field: &[u8]) -> Result<String, ParseReplyError> {
+    Ok(String::from_utf8(str_field.to_vec())?)
+}
+
+fn parse_protobuf_bytes(bytes_field: &[u8]) -> Option<Binary> {
     if bytes_field.is_empty() {
-        Ok(None)
+        None
     } else {
-        Ok(Some(Binary(bytes_field)))
+        Some(Binary(bytes_field.to_vec()))
     }
 }
 
-pub fn parse_reply_instantiate_data(
-    msg: Reply,
-) -> Result<MsgInstantiateContractResponse, ParseReplyError> {
-    let data = msg
-        .result
+/// Returns the position of `inner` within `outer`, which it must borrow from
+fn subslice_range(outer: &[u8], inner: &[u8]) -> Range<usize> {
+    if inner.is_empty() {
+        return 0..0;
+    }
+    let start = inner.as_ptr() as usize - outer.as_ptr() as usize;
+    start..start + inner.len()
+}
+
+/// Cuts `range` out of the data, reusing its allocation
+fn into_subslice(data: Binary, range: Range<usize>) -> Option<Binary> {
+    if range.is_empty() {
+        return None;
+    }
+    let mut bytes = data.0;
+    bytes.truncate(range.end);
+    bytes.drain(..range.start);
+    Some(Binary(bytes))
+}
+
+/// Reads the fields of a MsgInstantiateContractResponse, without copying them.
+/// The last occurrence of a field wins, unknown fields are skipped.
+fn decode_instantiate_response(data: &[u8]) -> Result<(&[u8], &[u8]), ParseReplyError> {
+    let mut contract_address: &[u8] = &[];
+    let mut inner_data: &[u8] = &[];
+    for field in ProtoReader::new(data) {
+        match field? {
+            (1, _, value) => contract_address = length_delimited(value, 1)?,
+            (2, _, value) => inner_data = length_delimited(value, 2)?,
+            _ => {}
+        }
+    }
+    Ok((contract_address, inner_data))
+}
+
+/// Reads the data of a MsgExecuteContractResponse or MsgMigrateContractResponse,
+/// without copying it
+fn decode_data_response(data: &[u8]) -> Result<&[u8], ParseReplyError> {
+    let mut inner_data: &[u8] = &[];
+    for field in ProtoReader::new(data) {
+        if let (1, _, value) = field? {
+            inner_data = length_delimited(value, 1)?;
+        }
+    }
+    Ok(inner_data)
+}
+
+fn reply_data(msg: Reply) -> Result<Binary, ParseReplyError> {
+    msg.result
         .into_result()
         .map_err(ParseReplyError::SubMsgFailure)?
         .data
-        .ok_or_else(|| ParseReplyError::ParseFailure("Missing reply data".to_owned()))?;
-    parse_instantiate_response_data(&data.0)
+        .ok_or_else(|| ParseReplyError::ParseFailure("Missing reply data".to_owned()))
+}
+
+pub fn parse_reply_instantiate_data(
+    msg: Reply,
+) -> Result<MsgInstantiateContractResponse, ParseReplyError> {
+    let data = reply_data(msg)?;
+    let (contract_address, inner_data) = decode_instantiate_response(&data)?;
+    let contract_address = parse_protobuf_string(contract_address)?;
+    // the inner data takes over the reply's buffer
+    let range = subslice_range(&data, inner_data);
+    Ok(MsgInstantiateContractResponse {
+        contract_address,
+        data: into_subslice(data, range),
+    })
 }
 
 pub fn parse_reply_execute_data(msg: Reply) -> Result<MsgExecuteContractResponse, ParseReplyError> {
+    let data = reply_data(msg)?;
+    let inner_data = decode_data_response(&data)?;
+    // the inner data takes over the reply's buffer
+    let range = subslice_range(&data, inner_data);
+    Ok(MsgExecuteContractResponse {
+        data: into_subslice(data, range),
+    })
+}
+
+pub fn parse_instantiate_response_data(
+    data: &[u8],
+) -> Result<MsgInstantiateContractResponse, ParseReplyError> {
+    let (contract_address, inner_data) = decode_instantiate_response(data)?;
+    Ok(MsgInstantiateContractResponse {
+        contract_address: parse_protobuf_string(contract_address)?,
+        data: parse_protobuf_bytes(inner_data),
+    })
+}
+
+pub fn parse_execute_response_data(
+    data: &[u8],
+) -> Result<MsgExecuteContractResponse, ParseReplyError> {
+    let inner_data = decode_data_response(data)?;
+    Ok(MsgExecuteContractResponse {
+        data: parse_protobuf_bytes(inner_data),
+    })
+}
+
+pub fn parse_reply_instantiate2_data(
+    msg: Reply,
+) -> Result<MsgInstantiateContract2Response, ParseReplyError> {
+    parse_reply_instantiate_data(msg)
+}
+
+pub fn parse_reply_migrate_data(msg: Reply) -> Result<MsgMigrateContractResponse, ParseReplyError> {
+    let data = reply_data(msg)?;
+    let inner_data = decode_data_response(&data)?;
+    // the inner data takes over the reply's buffer
+    let range = subslice_range(&data, inner_data);
+    Ok(MsgMigrateContractResponse {
+        data: into_subslice(data, range),
+    })
+}
+
+pub fn parse_reply_store_code_data(msg: Reply) -> Result<MsgStoreCodeResponse, ParseReplyError> {
+    let data = reply_data(msg)?;
+    parse_store_code_response_data(&data)
+}
+
+pub fn parse_instantiate2_response_data(
+    data: &[u8],
+) -> Result<MsgInstantiateContract2Response, ParseReplyError> {
+    parse_instantiate_response_data(data)
+}
+
+pub fn parse_migrate_response_data(
+    data: &[u8],
+) -> Result<MsgMigrateContractResponse, ParseReplyError> {
+    let inner_data = decode_data_response(data)?;
+    Ok(MsgMigrateContractResponse {
+        data: parse_protobuf_bytes(inner_data),
+    })
+}
+
+pub fn parse_store_code_response_data(
+    data: &[u8],
+) -> Result<MsgStoreCodeResponse, ParseReplyError> {
+    let mut code_id = 0;
+    let mut checksum: &[u8] = &[];
+    for field in ProtoReader::new(data) {
+        match field? {
+            (1, _, value) => code_id = varint(value, 1)?,
+            (2, _, value) => checksum = length_delimited(value, 2)?,
+            // skip unknown fields
+            _ => {}
+        }
+    }
+
+    // code ids start at 1, and proto3 leaves out a zero
+    if code_id == 0 {
+        return Err(decode_failure("field #1: missing code id".to_owned()));
+    }
+    if checksum.is_empty() {
+        return Err(decode_failure("field #2: missing checksum".to_owned()));
+    }
+    Ok(MsgStoreCodeResponse {
+        code_id,
+        checksum: Binary(checksum.to_vec()),
+    })
+}
+
+pub fn parse_reply_transfer_data(msg: Reply) -> Result<MsgTransferResponse, ParseReplyError> {
+    let data = reply_data(msg)?;
+    parse_transfer_response_data(&data)
+}
+
+pub fn parse_reply_delegate_data(msg: Reply) -> Result<MsgDelegateResponse, ParseReplyError> {
+    // the response is empty, so the reply may have no data at all
     let data = msg
         .result
         .into_result()
         .map_err(ParseReplyError::SubMsgFailure)?
         .data
-        .ok_or_else(|| ParseReplyError::ParseFailure("Missing reply data".to_owned()))?;
-    parse_execute_response_data(&data.0)
+        .unwrap_or_default();
+    parse_delegate_response_data(&data)
 }
 
-pub fn parse_instantiate_response_data(
-    data: &[u8],
-) -> Result<MsgInstantiateContractResponse, ParseReplyError> {
-    // Manual protobuf decoding
-    let mut data = data.to_vec();
-    // Parse contract addr
-    let contract_addr = parse_protobuf_string(&mut data, 1)?;
+pub fn parse_reply_undelegate_data(msg: Reply) -> Result<MsgUndelegateResponse, ParseReplyError> {
+    let data = reply_data(msg)?;
+    parse_undelegate_response_data(&data)
+}
 
-    // Parse (optional) data
-    let data = parse_protobuf_bytes(&mut data, 2)?;
+pub fn parse_reply_create_denom_data(
+    msg: Reply,
+) -> Result<MsgCreateDenomResponse, ParseReplyError> {
+    let data = reply_data(msg)?;
+    parse_create_denom_response_data(&data)
+}
 
-    Ok(MsgInstantiateContractResponse {
-        contract_address: contract_addr,
-        data,
+pub fn parse_transfer_response_data(data: &[u8]) -> Result<MsgTransferResponse, ParseReplyError> {
+    let mut sequence = 0;
+    for field in ProtoReader::new(data) {
+        if let (1, _, value) = field? {
+            sequence = varint(value, 1)?;
+        }
+    }
+    Ok(MsgTransferResponse { sequence })
+}
+
+pub fn parse_delegate_response_data(data: &[u8]) -> Result<MsgDelegateResponse, ParseReplyError> {
+    // no known fields, but the data must still be valid protobuf
+    for field in ProtoReader::new(data) {
+        field?;
+    }
+    Ok(MsgDelegateResponse {})
+}
+
+pub fn parse_undelegate_response_data(
+    data: &[u8],
+) -> Result<MsgUndelegateResponse, ParseReplyError> {
+    let mut completion_time = None;
+    let mut amount = None;
+    for field in ProtoReader::new(data) {
+        match field? {
+            (1, _, value) => {
+                completion_time = Some(parse_timestamp_data(length_delimited(value, 1)?)?)
+            }
+            (2, _, value) => amount = Some(parse_coin_data(length_delimited(value, 2)?)?),
+            // skip unknown fields
+            _ => {}
+        }
+    }
+    let completion_time = completion_time
+        .ok_or_else(|| decode_failure("field #1: missing completion time".to_owned()))?;
+    Ok(MsgUndelegateResponse {
+        completion_time,
+        amount,
     })
 }
 
-pub fn parse_execute_response_data(
+pub fn parse_create_denom_response_data(
     data: &[u8],
-) -> Result<MsgExecuteContractResponse, ParseReplyError> {
-    // Manual protobuf decoding
-    let mut data = data.to_vec();
-    let inner_data = parse_protobuf_bytes(&mut data, 1)?;
+) -> Result<MsgCreateDenomResponse, ParseReplyError> {
+    let mut new_token_denom: &[u8] = &[];
+    for field in ProtoReader::new(data) {
+        if let (1, _, value) = field? {
+            new_token_denom = length_delimited(value, 1)?;
+        }
+    }
+    Ok(MsgCreateDenomResponse {
+        new_token_denom: parse_protobuf_string(new_token_denom)?,
+    })
+}
 
-    Ok(MsgExecuteContractResponse { data: inner_data })
+/// Parses an encoded `google.protobuf.Timestamp`. Times before 1970 are not supported.
+pub fn parse_timestamp_data(data: &[u8]) -> Result<Timestamp, ParseReplyError> {
+    let mut seconds = 0;
+    let mut nanos = 0;
+    for field in ProtoReader::new(data) {
+        match field? {
+            (1, _, value) => seconds = varint(value, 1)?,
+            (2, _, value) => nanos = varint(value, 2)?,
+            _ => {}
+        }
+    }
+    // int64 values are encoded as two's complement
+    if (seconds as i64) < 0 {
+        return Err(decode_failure(format!(
+            "field #1: negative timestamp {}",
+            seconds as i64
+        )));
+    }
+    if nanos >= 1_000_000_000 {
+        return Err(decode_failure(format!(
+            "field #2: invalid nanos {}",
+            nanos as i64
+        )));
+    }
+    // Timestamp counts nanoseconds in a u64, so it ends in the year 2554
+    seconds
+        .checked_mul(1_000_000_000)
+        .and_then(|secs| secs.checked_add(nanos))
+        .map(Timestamp::from_nanos)
+        .ok_or_else(|| {
+            decode_failure(format!(
+                "field #1: timestamp {}.{:09} out of range",
+                seconds, nanos
+            ))
+        })
+}
+
+/// Parses an encoded `cosmos.base.v1beta1.Coin`
+pub fn parse_coin_data(data: &[u8]) -> Result<Coin, ParseReplyError> {
+    let mut denom: &[u8] = &[];
+    let mut amount: &[u8] = &[];
+    for field in ProtoReader::new(data) {
+        match field? {
+            (1, _, value) => denom = length_delimited(value, 1)?,
+            (2, _, value) => amount = length_delimited(value, 2)?,
+            _ => {}
+        }
+    }
+    let amount = parse_protobuf_string(amount)?;
+    let amount = if amount.is_empty() {
+        Uint128::zero()
+    } else {
+        amount
+            .parse()
+            .map_err(|_| decode_failure(format!("field #2: invalid amount {}", amount)))?
+    };
+    Ok(Coin {
+        denom: parse_protobuf_string(denom)?,
+        amount,
+    })
 }
 
 #[derive(Error, Debug, PartialEq, Eq)]
@@ -218,96 +844,161 @@ mod test {
         pub data: ::prost::alloc::vec::Vec<u8>,
     }
 
+    /// Reads the first field, returning it with the remaining data
+    fn read_first(data: &[u8]) -> Result<(ProtoField<'_>, &[u8]), ParseReplyError> {
+        let mut reader = ProtoReader::new(data);
+        let field = reader.read_field()?.unwrap();
+        Ok((field, reader.remaining()))
+    }
+
     #[test]
-    fn parse_protobuf_varint_tests() {
-        let field_number = 1;
+    fn proto_reader_varint_tests() {
         // Single-byte varint works
-        let mut data = b"\x0a".to_vec();
-        let len = parse_protobuf_varint(&mut data, field_number).unwrap();
-        assert_eq!(len, 10);
+        let (field, rest) = read_first(b"\x08\x0a").unwrap();
+        assert_eq!(field, (1, WIRE_TYPE_VARINT, ProtoValue::Varint(10)));
+        assert_eq!(rest, b"");
 
         // Rest is returned
-        let mut data = b"\x0a\x0b".to_vec();
-        let len = parse_protobuf_varint(&mut data, field_number).unwrap();
-        assert_eq!(len, 10);
-        assert_eq!(data, b"\x0b".to_vec());
+        let (field, rest) = read_first(b"\x08\x0a\x0b").unwrap();
+        assert_eq!(field.2.as_varint(), Some(10));
+        assert_eq!(rest, b"\x0b");
 
         // Multi-byte varint works
         // 300 % 128 = 44. 44 + 128 = 172 (0xac) (1st byte)
         // 300 / 128 = 2 (x02) (2nd byte)
-        let mut data = b"\xac\x02".to_vec();
-        let len = parse_protobuf_varint(&mut data, field_number).unwrap();
-        assert_eq!(len, 300);
+        let (field, rest) = read_first(b"\x08\xac\x02\x0c").unwrap();
+        assert_eq!(field.2, ProtoValue::Varint(300));
+        assert_eq!(rest, b"\x0c");
 
-        // Rest is returned
-        let mut data = b"\xac\x02\x0c".to_vec();
-        let len = parse_protobuf_varint(&mut data, field_number).unwrap();
-        assert_eq!(len, 300);
-        assert_eq!(data, b"\x0c".to_vec());
+        // Max value works
+        let (field, _) = read_first(b"\x08\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01").unwrap();
+        assert_eq!(field.2, ProtoValue::Varint(u64::MAX));
+
+        // Multi-byte tag works
+        let (field, _) = read_first(b"\xa8\x02\x01").unwrap();
+        assert_eq!(field, (37, WIRE_TYPE_VARINT, ProtoValue::Varint(1)));
 
         // varint data too short (Empty varint)
-        let mut data = vec![];
-        let err = parse_protobuf_varint(&mut data, field_number).unwrap_err();
+        let err = read_first(b"\x08").unwrap_err();
         assert!(matches!(err, ParseFailure(..)));
 
         // varint data too short (Incomplete varint)
-        let mut data = b"\x80".to_vec();
-        let err = parse_protobuf_varint(&mut data, field_number).unwrap_err();
+        let err = read_first(b"\x08\x80").unwrap_err();
         assert!(matches!(err, ParseFailure(..)));
 
         // varint data too long
-        let mut data = b"\x80\x81\x82\x83\x84\x83\x82\x81\x80".to_vec();
-        let err = parse_protobuf_varint(&mut data, field_number).unwrap_err();
+        let err = read_first(b"\x08\x80\x81\x82\x83\x84\x83\x82\x81\x80\x80\x01").unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+
+        // varint overflows 64 bits
+        let err = read_first(b"\x08\xff\xff\xff\xff\xff\xff\xff\xff\xff\x02").unwrap_err();
         assert!(matches!(err, ParseFailure(..)));
     }
 
     #[test]
-    fn parse_protobuf_length_prefixed_tests() {
-        let field_number = 1;
+    fn proto_reader_length_delimited_tests() {
         // Single-byte length-prefixed works
-        let mut data = b"\x0a\x03abc".to_vec();
-        let res = parse_protobuf_length_prefixed(&mut data, field_number).unwrap();
-        assert_eq!(res, b"abc".to_vec());
-        assert_eq!(data, vec![0u8; 0]);
+        let (field, rest) = read_first(b"\x0a\x03abc").unwrap();
+        assert_eq!(
+            field,
+            (
+                1,
+                WIRE_TYPE_LENGTH_DELIMITED,
+                ProtoValue::LengthDelimited(b"abc")
+            )
+        );
+        assert_eq!(rest, b"");
 
         // Rest is returned
-        let mut data = b"\x0a\x03abcd".to_vec();
-        let res = parse_protobuf_length_prefixed(&mut data, field_number).unwrap();
-        assert_eq!(res, b"abc".to_vec());
-        assert_eq!(data, b"d".to_vec());
+        let (field, rest) = read_first(b"\x0a\x03abcd").unwrap();
+        assert_eq!(field.2.as_bytes(), Some(b"abc".as_slice()));
+        assert_eq!(rest, b"d");
 
         // Multi-byte length-prefixed works
-        let mut data = [b"\x0a\xac\x02", vec![65u8; 300].as_slice()]
-            .concat()
-            .to_vec();
-        let res = parse_protobuf_length_prefixed(&mut data, field_number).unwrap();
-        assert_eq!(res, vec![65u8; 300]);
-        assert_eq!(data, vec![0u8; 0]);
-
-        // Rest is returned
-        let mut data = [b"\x0a\xac\x02", vec![65u8; 300].as_slice(), b"rest"]
-            .concat()
-            .to_vec();
-        let res = parse_protobuf_length_prefixed(&mut data, field_number).unwrap();
-        assert_eq!(res, vec![65u8; 300]);
-        assert_eq!(data, b"rest");
+        let data = [b"\x0a\xac\x02", vec![65u8; 300].as_slice(), b"rest"].concat();
+        let (field, rest) = read_first(&data).unwrap();
+        assert_eq!(field.2.as_bytes(), Some(vec![65u8; 300].as_slice()));
+        assert_eq!(rest, b"rest");
 
         // message too short
-        let mut data = b"\x0a\x01".to_vec();
-        let field_number = 1;
-        let err = parse_protobuf_length_prefixed(&mut data, field_number).unwrap_err();
+        let err = read_first(b"\x0a\x01").unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+
+        // length too short
+        let err = read_first(b"\x0a").unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+    }
+
+    #[test]
+    fn proto_reader_fixed_tests() {
+        let (field, rest) = read_first(b"\x09\x01\x02\x00\x00\x00\x00\x00\x80rest").unwrap();
+        assert_eq!(
+            field,
+            (
+                1,
+                WIRE_TYPE_FIXED64,
+                ProtoValue::Fixed64(0x8000_0000_0000_0201)
+            )
+        );
+        assert_eq!(rest, b"rest");
+
+        let (field, rest) = read_first(b"\x15\x01\x02\x00\x80").unwrap();
+        assert_eq!(
+            field,
+            (2, WIRE_TYPE_FIXED32, ProtoValue::Fixed32(0x8000_0201))
+        );
+        assert_eq!(rest, b"");
+        assert_eq!(field.2.as_bytes(), None);
+        assert_eq!(field.2.as_varint(), None);
+
+        // too short
+        let err = read_first(b"\x09\x01\x02\x03").unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+        let err = read_first(b"\x15\x01").unwrap_err();
         assert!(matches!(err, ParseFailure(..)));
+    }
 
-        // invalid wire type
-        let mut data = b"\x0b\x01a".to_vec();
-        let err = parse_protobuf_length_prefixed(&mut data, field_number).unwrap_err();
+    #[test]
+    fn proto_reader_invalid_tags() {
+        // invalid wire type (start group)
+        let err = read_first(b"\x0b\x01a").unwrap_err();
         assert!(matches!(err, ParseFailure(..)));
 
         // invalid field number
-        let field_number = 2;
-        let mut data = b"\x0a\x01a".to_vec();
-        let err = parse_protobuf_length_prefixed(&mut data, field_number).unwrap_err();
+        let err = read_first(b"\x02\x01a").unwrap_err();
         assert!(matches!(err, ParseFailure(..)));
+
+        // empty message has no fields
+        assert_eq!(ProtoReader::new(b"").read_field().unwrap(), None);
+    }
+
+    #[test]
+    fn proto_reader_iterates_all_fields() {
+        let data = b"\x12\x02ab\x08\x05\x0d\x01\x00\x00\x00\x0a\x00";
+        let fields: Vec<_> = ProtoReader::new(data).collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                (
+                    2,
+                    WIRE_TYPE_LENGTH_DELIMITED,
+                    ProtoValue::LengthDelimited(b"ab")
+                ),
+                (1, WIRE_TYPE_VARINT, ProtoValue::Varint(5)),
+                (1, WIRE_TYPE_FIXED32, ProtoValue::Fixed32(1)),
+                (
+                    1,
+                    WIRE_TYPE_LENGTH_DELIMITED,
+                    ProtoValue::LengthDelimited(b"")
+                ),
+            ]
+        );
+
+        // stops after an error
+        let mut reader = ProtoReader::new(b"\x08\x01\x0b\x08\x02");
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
     }
 
     #[test]
@@ -316,32 +1007,35 @@ mod test {
 
         // Empty works
         let data = vec![];
-        let mut encoded_data = encode_bytes(&data);
-
-        let res = parse_protobuf_bytes(&mut encoded_data, field_number).unwrap();
-        assert_eq!(res, None);
+        let encoded_data = encode_bytes(&data);
+        assert_eq!(encoded_data, b"");
+        assert_eq!(ProtoReader::new(&encoded_data).read_field().unwrap(), None);
 
         // Simple works
         let data = b"test".to_vec();
-        let mut encoded_data = encode_bytes(&data);
+        let encoded_data = encode_bytes(&data);
 
-        let res = parse_protobuf_bytes(&mut encoded_data, field_number).unwrap();
+        let ((_, _, value), _) = read_first(&encoded_data).unwrap();
+        let res = parse_protobuf_bytes(length_delimited(value, field_number).unwrap());
         assert_eq!(res, Some(Binary(data)));
 
         // Large works
         let data = vec![0x40; 300];
-        let mut encoded_data = encode_bytes(&data);
+        let encoded_data = encode_bytes(&data);
 
-        let res = parse_protobuf_bytes(&mut encoded_data, field_number).unwrap();
+        let ((_, _, value), _) = read_first(&encoded_data).unwrap();
+        let res = parse_protobuf_bytes(length_delimited(value, field_number).unwrap());
         assert_eq!(res, Some(Binary(data)));
 
         // Field number works
         let field_number = 5;
         let data = b"test field 5".to_vec();
         let mut encoded_data = encode_bytes(&data);
-        encoded_data[0] = (field_number << 3) + WIRE_TYPE_LENGTH_DELIMITED;
+        encoded_data[0] = ((field_number as u8) << 3) + WIRE_TYPE_LENGTH_DELIMITED;
 
-        let res = parse_protobuf_bytes(&mut encoded_data, field_number).unwrap();
+        let ((field, _, value), _) = read_first(&encoded_data).unwrap();
+        assert_eq!(field, field_number);
+        let res = parse_protobuf_bytes(length_delimited(value, field_number).unwrap());
         assert_eq!(res, Some(Binary(data)));
 
         // Remainder is kept
@@ -351,44 +1045,46 @@ mod test {
         let mut encoded_data = encode_bytes(&data);
         encoded_data[1] = test_len as u8;
 
-        let res = parse_protobuf_bytes(&mut encoded_data, field_number).unwrap();
+        let ((_, _, value), rest) = read_first(&encoded_data).unwrap();
+        let res = parse_protobuf_bytes(length_delimited(value, field_number).unwrap());
         assert_eq!(res, Some(Binary(data[..test_len].to_owned())));
-        assert_eq!(encoded_data, data[test_len..].to_owned());
+        assert_eq!(rest, &data[test_len..]);
+
+        // Invalid wire type errs
+        let err = length_delimited(ProtoValue::Varint(1), field_number).unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
     }
 
     #[test]
     fn parse_protobuf_string_tests() {
         let field_number = 1;
 
-        // Empty works
-        let data = "";
-        let mut encoded_data = encode_string(data);
-
-        let res = parse_protobuf_string(&mut encoded_data, field_number).unwrap();
-        assert_eq!(res, data);
-
         // Simple works
         let data = "test";
-        let mut encoded_data = encode_string(data);
+        let encoded_data = encode_string(data);
 
-        let res = parse_protobuf_string(&mut encoded_data, field_number).unwrap();
+        let ((_, _, value), _) = read_first(&encoded_data).unwrap();
+        let res = parse_protobuf_string(length_delimited(value, field_number).unwrap()).unwrap();
         assert_eq!(res, data);
 
         // Large works
         let data = vec![0x40; 300];
         let str_data = from_utf8(data.as_slice()).unwrap();
-        let mut encoded_data = encode_string(str_data);
+        let encoded_data = encode_string(str_data);
 
-        let res = parse_protobuf_string(&mut encoded_data, field_number).unwrap();
+        let ((_, _, value), _) = read_first(&encoded_data).unwrap();
+        let res = parse_protobuf_string(length_delimited(value, field_number).unwrap()).unwrap();
         assert_eq!(res, str_data);
 
         // Field number works
         let field_number = 5;
         let data = "test field 5";
         let mut encoded_data = encode_string(data);
-        encoded_data[0] = (field_number << 3) + WIRE_TYPE_LENGTH_DELIMITED;
+        encoded_data[0] = ((field_number as u8) << 3) + WIRE_TYPE_LENGTH_DELIMITED;
 
-        let res = parse_protobuf_string(&mut encoded_data, field_number).unwrap();
+        let ((field, _, value), _) = read_first(&encoded_data).unwrap();
+        assert_eq!(field, field_number);
+        let res = parse_protobuf_string(length_delimited(value, field_number).unwrap()).unwrap();
         assert_eq!(res, data);
 
         // Remainder is kept
@@ -398,17 +1094,537 @@ mod test {
         let mut encoded_data = encode_string(data);
         encoded_data[1] = test_len as u8;
 
-        let res = parse_protobuf_string(&mut encoded_data, field_number).unwrap();
+        let ((_, _, value), rest) = read_first(&encoded_data).unwrap();
+        let res = parse_protobuf_string(length_delimited(value, field_number).unwrap()).unwrap();
         assert_eq!(res, data[..test_len]);
-        assert_eq!(encoded_data, data[test_len..].as_bytes());
+        assert_eq!(rest, &data.as_bytes()[test_len..]);
 
         // Broken utf-8 errs
-        let field_number = 1;
         let data = "test_X";
         let mut encoded_data = encode_string(data);
         let encoded_len = encoded_data.len();
         encoded_data[encoded_len - 1] = 0xd3;
-        let err = parse_protobuf_string(&mut encoded_data, field_number).unwrap_err();
+        let ((_, _, value), _) = read_first(&encoded_data).unwrap();
+        let err =
+            parse_protobuf_string(length_delimited(value, field_number).unwrap()).unwrap_err();
+        assert!(matches!(err, BrokenUtf8(..)));
+    }
+
+    #[test]
+    fn parse_response_data_any_field_order() {
+        // data before contract address, with an unknown varint field in between
+        let data = b"\x12\x02ab\x18\x05\x0a\x01c";
+        let res = parse_instantiate_response_data(data).unwrap();
+        assert_eq!(
+            res,
+            super::MsgInstantiateContractResponse {
+                contract_address: "c".to_string(),
+                data: Some(Binary(b"ab".to_vec())),
+            }
+        );
+
+        // missing fields are empty
+        let res = parse_instantiate_response_data(b"").unwrap();
+        assert_eq!(res.contract_address, "");
+        assert_eq!(res.data, None);
+
+        // unknown fields of every wire type are skipped
+        let data = b"\x11\x01\x02\x03\x04\x05\x06\x07\x08\x1d\x01\x02\x03\x04\x22\x01z\x0a\x01x";
+        let res = parse_execute_response_data(data).unwrap();
+        assert_eq!(res.data, Some(Binary(b"x".to_vec())));
+
+        // known fields must have the right wire type
+        let err = parse_execute_response_data(b"\x08\x01").unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+    }
+
+    #[test]
+    fn parse_reply_matches_response_data() {
+        let reply = |data: &[u8]| Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary(data.to_vec())),
+            }),
+        };
+        for data in [
+            b"".as_slice(),
+            b"\x0a\x01c",
+            b"\x12\x02ab\x18\x05\x0a\x01c",
+            b"\x0a\x01c\x12\x03abc\x12\x02de\x22\x01z",
+            b"\x12\x01a\x12\x00",
+        ] {
+            assert_eq!(
+                parse_reply_instantiate_data(reply(data)).unwrap(),
+                parse_instantiate_response_data(data).unwrap()
+            );
+            assert_eq!(
+                parse_reply_execute_data(reply(data)).unwrap(),
+                parse_execute_response_data(data).unwrap()
+            );
+        }
+
+        // the last occurrence wins
+        let res = parse_reply_instantiate_data(reply(b"\x12\x03abc\x0a\x01c\x12\x02de")).unwrap();
+        assert_eq!(res.data, Some(Binary(b"de".to_vec())));
+        let res = parse_reply_execute_data(reply(b"\x0a\x03abc\x12\x02de")).unwrap();
+        assert_eq!(res.data, Some(Binary(b"abc".to_vec())));
+
+        let err = parse_reply_execute_data(reply(b"\x0a\x05abc")).unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+        let err = parse_reply_execute_data(Reply {
+            id: 1,
+            result: SubMsgResult::Err("failed".to_string()),
+        })
+        .unwrap_err();
+        assert_eq!(err, ParseReplyError::SubMsgFailure("failed".to_string()));
+    }
+
+    #[test]
+    fn proto_writer_round_trip() {
+        let mut writer = ProtoWriter::new();
+        writer
+            .write_varint(1, 300)
+            .write_varint(2, u64::MAX)
+            .write_varint(3, 0)
+            .write_string(4, "abc")
+            .write_length_delimited(5, b"")
+            .write_length_delimited(u32::MAX >> 3, &[7u8; 200]);
+        let data = writer.into_vec();
+        let fields: Vec<_> = ProtoReader::new(&data).collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                (1, WIRE_TYPE_VARINT, ProtoValue::Varint(300)),
+                (2, WIRE_TYPE_VARINT, ProtoValue::Varint(u64::MAX)),
+                (
+                    4,
+                    WIRE_TYPE_LENGTH_DELIMITED,
+                    ProtoValue::LengthDelimited(b"abc")
+                ),
+                (
+                    u32::MAX >> 3,
+                    WIRE_TYPE_LENGTH_DELIMITED,
+                    ProtoValue::LengthDelimited(&[7u8; 200])
+                ),
+            ]
+        );
+
+        // same encoding as prost
+        let mut writer = ProtoWriter::new();
+        writer.write_length_delimited(1, &[1u8; 300]);
+        assert_eq!(writer.into_vec(), encode_bytes(&[1u8; 300]));
+        let mut writer = ProtoWriter::new();
+        writer.write_string(1, "test");
+        assert_eq!(writer.into_vec(), encode_string("test"));
+
+        assert!(ProtoWriter::new().is_empty());
+
+        // field numbers beyond 29 bits are rejected by the reader
+        let err = read_first(b"\x80\x80\x80\x80\x10\x00").unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid protobuf field number 0")]
+    fn proto_writer_rejects_field_zero() {
+        ProtoWriter::new().write_varint(0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid protobuf field number 536870912")]
+    fn proto_writer_rejects_large_field() {
+        // even if the empty value would not be written
+        ProtoWriter::new().write_string(1 << 29, "");
+    }
+
+    #[test]
+    fn encode_responses_round_trip() {
+        for (contract_address, data) in [
+            ("", None),
+            ("Contract #1", None),
+            ("Contract #1", Some(Binary(vec![1u8, 2, 255, 7, 5]))),
+            ("contract", Some(Binary(vec![2u8; 32769]))),
+        ] {
+            let instantiate = super::MsgInstantiateContractResponse {
+                contract_address: contract_address.to_string(),
+                data: data.clone(),
+            };
+            let encoded = instantiate.encode();
+            assert_eq!(
+                parse_instantiate_response_data(&encoded).unwrap(),
+                instantiate
+            );
+            let prost_encoded = MsgInstantiateContractResponse {
+                contract_address: contract_address.to_string(),
+                data: data.clone().unwrap_or_default().0,
+            }
+            .encode_to_vec();
+            assert_eq!(encoded, prost_encoded);
+
+            let execute = super::MsgExecuteContractResponse { data: data.clone() };
+            let reply = Reply {
+                id: 1,
+                result: SubMsgResult::Ok(SubMsgResponse {
+                    events: vec![],
+                    data: Some(execute.to_binary_proto()),
+                }),
+            };
+            assert_eq!(parse_reply_execute_data(reply).unwrap(), execute);
+            let prost_encoded = MsgExecuteContractResponse {
+                data: data.unwrap_or_default().0,
+            }
+            .encode_to_vec();
+            assert_eq!(execute.encode(), prost_encoded);
+        }
+
+        // empty data is left out, and read back as None
+        let execute = super::MsgExecuteContractResponse {
+            data: Some(Binary(vec![])),
+        };
+        assert_eq!(execute.encode(), Vec::<u8>::new());
+        assert_eq!(
+            parse_execute_response_data(&execute.encode()).unwrap(),
+            super::MsgExecuteContractResponse { data: None }
+        );
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct MsgInstantiateContract2Response {
+        #[prost(string, tag = "1")]
+        pub address: ::prost::alloc::string::String,
+        #[prost(bytes, tag = "2")]
+        pub data: ::prost::alloc::vec::Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct MsgMigrateContractResponse {
+        #[prost(bytes, tag = "1")]
+        pub data: ::prost::alloc::vec::Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct MsgStoreCodeResponse {
+        #[prost(uint64, tag = "1")]
+        pub code_id: u64,
+        #[prost(bytes, tag = "2")]
+        pub checksum: ::prost::alloc::vec::Vec<u8>,
+    }
+
+    fn ok_reply(data: Vec<u8>) -> Reply {
+        Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(data.into()),
+            }),
+        }
+    }
+
+    #[test]
+    fn parse_reply_instantiate2_data_works() {
+        for data in [vec![], vec![1u8, 2, 255, 7, 5], vec![3u8; 257]] {
+            let encoded = MsgInstantiateContract2Response {
+                address: "predictable address".to_string(),
+                data: data.clone(),
+            }
+            .encode_to_vec();
+            let expected = super::MsgInstantiateContract2Response {
+                contract_address: "predictable address".to_string(),
+                data: parse_protobuf_bytes(&data),
+            };
+
+            let res = parse_instantiate2_response_data(&encoded).unwrap();
+            assert_eq!(res, expected);
+            let res = parse_reply_instantiate2_data(ok_reply(encoded)).unwrap();
+            assert_eq!(res, expected);
+        }
+    }
+
+    #[test]
+    fn parse_reply_migrate_data_works() {
+        for data in [vec![], vec![1u8, 2, 3, 127, 15], vec![2u8; 32769]] {
+            let encoded = MsgMigrateContractResponse { data: data.clone() }.encode_to_vec();
+            let expected = super::MsgMigrateContractResponse {
+                data: parse_protobuf_bytes(&data),
+            };
+            assert_eq!(expected.encode(), encoded);
+
+            let res = parse_migrate_response_data(&encoded).unwrap();
+            assert_eq!(res, expected);
+            let res = parse_reply_migrate_data(ok_reply(encoded)).unwrap();
+            assert_eq!(res, expected);
+        }
+
+        let err = parse_reply_migrate_data(Reply {
+            id: 1,
+            result: SubMsgResult::Err("migrate failed".to_string()),
+        })
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ParseReplyError::SubMsgFailure("migrate failed".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_reply_store_code_data_works() {
+        for (code_id, checksum) in [(1, vec![0xabu8; 32]), (u64::MAX, vec![0x01; 32])] {
+            let encoded = MsgStoreCodeResponse {
+                code_id,
+                checksum: checksum.clone(),
+            }
+            .encode_to_vec();
+            let expected = super::MsgStoreCodeResponse {
+                code_id,
+                checksum: Binary(checksum),
+            };
+            assert_eq!(expected.encode(), encoded);
+
+            let res = parse_store_code_response_data(&encoded).unwrap();
+            assert_eq!(res, expected);
+            let res = parse_reply_store_code_data(ok_reply(encoded)).unwrap();
+            assert_eq!(res, expected);
+        }
+
+        // code id must be a varint
+        let err = parse_store_code_response_data(b"\x0a\x01a").unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+        // checksum must be length-delimited
+        let err = parse_store_code_response_data(b"\x08\x01\x10\x01").unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+
+        // both fields are required
+        for (code_id, checksum) in [(0, vec![0xabu8; 32]), (1, vec![]), (0, vec![])] {
+            let encoded = MsgStoreCodeResponse { code_id, checksum }.encode_to_vec();
+            let err = parse_store_code_response_data(&encoded).unwrap_err();
+            assert!(matches!(err, ParseFailure(..)));
+            let err = parse_reply_store_code_data(ok_reply(encoded)).unwrap_err();
+            assert!(matches!(err, ParseFailure(..)));
+        }
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct ProtoTimestamp {
+        #[prost(int64, tag = "1")]
+        pub seconds: i64,
+        #[prost(int32, tag = "2")]
+        pub nanos: i32,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct ProtoCoin {
+        #[prost(string, tag = "1")]
+        pub denom: ::prost::alloc::string::String,
+        #[prost(string, tag = "2")]
+        pub amount: ::prost::alloc::string::String,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct MsgTransferResponse {
+        #[prost(uint64, tag = "1")]
+        pub sequence: u64,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct MsgUndelegateResponse {
+        #[prost(message, optional, tag = "1")]
+        pub completion_time: Option<ProtoTimestamp>,
+        #[prost(message, optional, tag = "2")]
+        pub amount: Option<ProtoCoin>,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct MsgCreateDenomResponse {
+        #[prost(string, tag = "1")]
+        pub new_token_denom: ::prost::alloc::string::String,
+    }
+
+    #[test]
+    fn parse_timestamp_data_works() {
+        for (seconds, nanos) in [(0, 0), (1_700_000_000, 0), (1_700_000_000, 999_999_999)] {
+            let encoded = ProtoTimestamp { seconds, nanos }.encode_to_vec();
+            let res = parse_timestamp_data(&encoded).unwrap();
+            assert_eq!(
+                res,
+                Timestamp::from_seconds(seconds as u64).plus_nanos(nanos as u64)
+            );
+            assert_eq!(encode_timestamp(&res), encoded);
+        }
+
+        let encoded = ProtoTimestamp {
+            seconds: -1,
+            nanos: 0,
+        }
+        .encode_to_vec();
+        let err = parse_timestamp_data(&encoded).unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+
+        for nanos in [-1, 1_000_000_000] {
+            let encoded = ProtoTimestamp { seconds: 1, nanos }.encode_to_vec();
+            let err = parse_timestamp_data(&encoded).unwrap_err();
+            assert!(matches!(err, ParseFailure(..)));
+        }
+
+        // the last representable nanosecond
+        let max_seconds = (u64::MAX / 1_000_000_000) as i64;
+        let max_nanos = (u64::MAX % 1_000_000_000) as i32;
+        let encoded = ProtoTimestamp {
+            seconds: max_seconds,
+            nanos: max_nanos,
+        }
+        .encode_to_vec();
+        assert_eq!(
+            parse_timestamp_data(&encoded).unwrap(),
+            Timestamp::from_nanos(u64::MAX)
+        );
+
+        // out of range, like the end of year 9999, errors instead of overflowing
+        for (seconds, nanos) in [
+            (max_seconds, max_nanos + 1),
+            (max_seconds + 1, 0),
+            (253_402_300_799, 0),
+            (i64::MAX, 999_999_999),
+        ] {
+            let encoded = ProtoTimestamp { seconds, nanos }.encode_to_vec();
+            let err = parse_timestamp_data(&encoded).unwrap_err();
+            assert!(matches!(err, ParseFailure(..)));
+        }
+    }
+
+    #[test]
+    fn parse_coin_data_works() {
+        let encoded = ProtoCoin {
+            denom: "uatom".to_string(),
+            amount: "340282366920938463463374607431768211455".to_string(),
+        }
+        .encode_to_vec();
+        let res = parse_coin_data(&encoded).unwrap();
+        assert_eq!(res, cosmwasm_std::coin(u128::MAX, "uatom"));
+        assert_eq!(encode_coin(&res), encoded);
+
+        let res = parse_coin_data(b"").unwrap();
+        assert_eq!(res, cosmwasm_std::coin(0, ""));
+
+        for amount in ["-5", "1.5", "340282366920938463463374607431768211456"] {
+            let encoded = ProtoCoin {
+                denom: "uatom".to_string(),
+                amount: amount.to_string(),
+            }
+            .encode_to_vec();
+            let err = parse_coin_data(&encoded).unwrap_err();
+            assert!(matches!(err, ParseFailure(..)));
+        }
+    }
+
+    #[test]
+    fn parse_reply_transfer_data_works() {
+        for sequence in [0, 1, 300, u64::MAX] {
+            let encoded = MsgTransferResponse { sequence }.encode_to_vec();
+            let expected = super::MsgTransferResponse { sequence };
+            assert_eq!(expected.encode(), encoded);
+
+            assert_eq!(parse_transfer_response_data(&encoded).unwrap(), expected);
+            assert_eq!(
+                parse_reply_transfer_data(ok_reply(encoded)).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn parse_reply_delegate_data_works() {
+        let expected = super::MsgDelegateResponse {};
+        assert_eq!(parse_delegate_response_data(b"").unwrap(), expected);
+        assert_eq!(
+            parse_reply_delegate_data(ok_reply(vec![])).unwrap(),
+            expected
+        );
+        let no_data = Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        };
+        assert_eq!(parse_reply_delegate_data(no_data).unwrap(), expected);
+        assert_eq!(expected.to_binary_proto(), Binary(vec![]));
+
+        let err = parse_delegate_response_data(b"\x0a\x05").unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+    }
+
+    #[test]
+    fn parse_reply_undelegate_data_works() {
+        let completion_time = ProtoTimestamp {
+            seconds: 1_700_000_000,
+            nanos: 123,
+        };
+        for amount in [
+            None,
+            Some(ProtoCoin {
+                denom: "uatom".to_string(),
+                amount: "1000000".to_string(),
+            }),
+        ] {
+            let encoded = MsgUndelegateResponse {
+                completion_time: Some(completion_time.clone()),
+                amount: amount.clone(),
+            }
+            .encode_to_vec();
+            let expected = super::MsgUndelegateResponse {
+                completion_time: Timestamp::from_seconds(1_700_000_000).plus_nanos(123),
+                amount: amount.map(|_| cosmwasm_std::coin(1_000_000, "uatom")),
+            };
+            assert_eq!(expected.encode(), encoded);
+
+            assert_eq!(parse_undelegate_response_data(&encoded).unwrap(), expected);
+            assert_eq!(
+                parse_reply_undelegate_data(ok_reply(encoded)).unwrap(),
+                expected
+            );
+        }
+
+        // the epoch is encoded as an empty message
+        let epoch = super::MsgUndelegateResponse {
+            completion_time: Timestamp::from_seconds(0),
+            amount: None,
+        };
+        assert_eq!(
+            parse_undelegate_response_data(&epoch.encode()).unwrap(),
+            epoch
+        );
+
+        // completion time is required
+        let err = parse_undelegate_response_data(b"").unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+        // broken nested message
+        let err = parse_undelegate_response_data(b"\x0a\x02\x08\x80").unwrap_err();
+        assert!(matches!(err, ParseFailure(..)));
+    }
+
+    #[test]
+    fn parse_reply_create_denom_data_works() {
+        let denom = "factory/osmo1creator/mytoken";
+        let encoded = MsgCreateDenomResponse {
+            new_token_denom: denom.to_string(),
+        }
+        .encode_to_vec();
+        let expected = super::MsgCreateDenomResponse {
+            new_token_denom: denom.to_string(),
+        };
+        assert_eq!(expected.encode(), encoded);
+
+        assert_eq!(
+            parse_create_denom_response_data(&encoded).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_reply_create_denom_data(ok_reply(encoded)).unwrap(),
+            expected
+        );
+
+        let err = parse_create_denom_response_data(b"\x0a\x01\xff").unwrap_err();
         assert!(matches!(err, BrokenUtf8(..)));
     }
 
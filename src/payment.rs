@@ -1,6 +1,10 @@
-use cosmwasm_std::{Coin, MessageInfo, Uint128};
+use std::collections::BTreeSet;
+
+use cosmwasm_std::{BankMsg, Coin, MessageInfo, Uint128};
 use thiserror::Error;
 
+use crate::{Asset, AssetInfo};
+
 /// returns an error if any coins were sent
 pub fn nonpayable(info: &MessageInfo) -> Result<(), PaymentError> {
     if info.funds.is_empty() {
@@ -52,6 +56,258 @@ pub fn may_pay(info: &MessageInfo, denom: &str) -> Result<Uint128, PaymentError>
     }
 }
 
+/// Requires exactly one denom sent, which matches `required.denom`, with at least
+/// `required.amount`. Returns the amount sent.
+pub fn must_pay_at_least(info: &MessageInfo, required: &Coin) -> Result<Uint128, PaymentError> {
+    let sent = must_pay(info, &required.denom)?;
+    if sent < required.amount {
+        return Err(PaymentError::InsufficientFunds {
+            denom: required.denom.clone(),
+            required: required.amount,
+            sent,
+        });
+    }
+    Ok(sent)
+}
+
+/// Requires exactly `required` to be sent, and nothing else.
+pub fn must_pay_exactly(info: &MessageInfo, required: &Coin) -> Result<(), PaymentError> {
+    let sent = must_pay_at_least(info, required)?;
+    if sent > required.amount {
+        return Err(PaymentError::Overpaid {
+            denom: required.denom.clone(),
+            required: required.amount,
+            sent,
+        });
+    }
+    Ok(())
+}
+
+/// Like must_pay_at_least, but returns a message refunding anything sent above
+/// `price` to the sender, or None if the exact price was paid.
+pub fn pay_with_change(info: &MessageInfo, price: &Coin) -> Result<Option<BankMsg>, PaymentError> {
+    let sent = must_pay_at_least(info, price)?;
+    let change = sent - price.amount;
+    if change.is_zero() {
+        return Ok(None);
+    }
+    Ok(Some(BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: vec![Coin {
+            denom: price.denom.clone(),
+            amount: change,
+        }],
+    }))
+}
+
+/// Requires all of the given denoms to be sent with a non-zero amount, and nothing else.
+/// Returns the amounts in the order of `denoms`.
+pub fn must_pay_many(info: &MessageInfo, denoms: &[&str]) -> Result<Vec<Uint128>, PaymentError> {
+    let funds = sent_funds(info)?;
+    if funds.is_empty() {
+        return Err(PaymentError::NoFunds {});
+    }
+    check_denoms(&funds, denoms)?;
+    denoms
+        .iter()
+        .map(|denom| {
+            find_amount(&funds, denom).ok_or_else(|| PaymentError::MissingDenom(denom.to_string()))
+        })
+        .collect()
+}
+
+/// Similar to must_pay_many, but every payment is optional. Returns an error if any other
+/// denom was sent. Otherwise, returns the amounts in the order of `denoms`, 0 if not sent.
+pub fn may_pay_many(info: &MessageInfo, denoms: &[&str]) -> Result<Vec<Uint128>, PaymentError> {
+    let funds = sent_funds(info)?;
+    check_denoms(&funds, denoms)?;
+    Ok(denoms
+        .iter()
+        .map(|denom| find_amount(&funds, denom).unwrap_or_default())
+        .collect())
+}
+
+/// Requires exactly the given coins to be sent, in any order.
+/// Returns an error on missing or extra denoms and on any difference in amount.
+/// An expected coin with zero amount means that denom must not be sent.
+pub fn must_pay_exact(info: &MessageInfo, expected: &[Coin]) -> Result<(), PaymentError> {
+    let funds = sent_funds(info)?;
+    let denoms: Vec<&str> = expected.iter().map(|c| c.denom.as_str()).collect();
+    check_denoms(&funds, &denoms)?;
+    for coin in expected {
+        let sent = find_amount(&funds, &coin.denom).unwrap_or_default();
+        // an expected zero amount requires that nothing of the denom was sent
+        if sent.is_zero() && !coin.amount.is_zero() {
+            return Err(PaymentError::MissingDenom(coin.denom.clone()));
+        }
+        if sent != coin.amount {
+            return Err(PaymentError::WrongAmount {
+                denom: coin.denom.clone(),
+                expected: coin.amount,
+                sent,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The way a message was paid: native funds, or a cw20 `Receive` hook
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AssetPayment<'a> {
+    Native(&'a MessageInfo),
+    /// `info` is the hook call, sent by the cw20 contract. `sender` and `amount` come from
+    /// the decoded `Cw20ReceiveMsg`.
+    Cw20 {
+        info: &'a MessageInfo,
+        sender: &'a str,
+        amount: Uint128,
+    },
+}
+
+impl<'a> AssetPayment<'a> {
+    /// Returns the account that paid, not the cw20 contract calling the hook
+    pub fn payer(&self) -> &str {
+        match self {
+            AssetPayment::Native(info) => info.sender.as_str(),
+            AssetPayment::Cw20 { sender, .. } => sender,
+        }
+    }
+}
+
+/// If exactly one asset was paid, returns it regardless of the token.
+/// Returns an error if nothing or more than one denom was sent, or if a cw20 hook
+/// also carried native funds.
+pub fn one_asset(payment: &AssetPayment) -> Result<Asset, PaymentError> {
+    match payment {
+        AssetPayment::Native(info) => one_coin(info).map(Asset::from),
+        AssetPayment::Cw20 { info, amount, .. } => {
+            if let Some(coin) = info.funds.first() {
+                return Err(PaymentError::ExtraDenom(coin.denom.clone()));
+            }
+            if amount.is_zero() {
+                return Err(PaymentError::NoFunds {});
+            }
+            Ok(Asset::cw20(*amount, info.sender.clone()))
+        }
+    }
+}
+
+/// Requires exactly one asset paid, which matches the requested token.
+/// Returns the amount if it is non-zero. Errors otherwise.
+pub fn must_pay_asset(payment: &AssetPayment, token: &AssetInfo) -> Result<Uint128, PaymentError> {
+    let asset = one_asset(payment)?;
+    if asset.info != *token {
+        return Err(match token {
+            AssetInfo::Native(denom) => PaymentError::MissingDenom(denom.clone()),
+            AssetInfo::Cw20(contract) => PaymentError::MissingCw20(contract.to_string()),
+        });
+    }
+    Ok(asset.amount)
+}
+
+/// PaymentPolicy describes which funds a message accepts, for contracts accepting
+/// more than one denom.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PaymentPolicy {
+    allowed: Vec<String>,
+    min_amounts: Vec<Coin>,
+    max_denoms: Option<usize>,
+    allow_zero: bool,
+}
+
+impl PaymentPolicy {
+    /// Creates a policy that accepts any non-empty payment
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a denom to the whitelist. Once any denom is allowed, all others are rejected.
+    pub fn allow(mut self, denom: impl Into<String>) -> Self {
+        self.allowed.push(denom.into());
+        self
+    }
+
+    /// Requires at least `min.amount` to be sent if `min.denom` is sent
+    pub fn min_amount(mut self, min: Coin) -> Self {
+        self.min_amounts.retain(|c| c.denom != min.denom);
+        self.min_amounts.push(min);
+        self
+    }
+
+    /// Limits how many different denoms can be sent at once
+    pub fn max_denoms(mut self, max: usize) -> Self {
+        self.max_denoms = Some(max);
+        self
+    }
+
+    /// Accepts messages without any funds
+    pub fn allow_zero(mut self, allow_zero: bool) -> Self {
+        self.allow_zero = allow_zero;
+        self
+    }
+
+    /// Checks the funds sent with the message against this policy.
+    /// Returns the funds sent, skipping zero amounts.
+    pub fn check(&self, info: &MessageInfo) -> Result<Vec<Coin>, PaymentError> {
+        let funds = sent_funds(info)?;
+        if funds.is_empty() && !self.allow_zero {
+            return Err(PaymentError::NoFunds {});
+        }
+        if !self.allowed.is_empty() {
+            let allowed: Vec<&str> = self.allowed.iter().map(String::as_str).collect();
+            check_denoms(&funds, &allowed)?;
+        }
+        if let Some(max) = self.max_denoms {
+            if funds.len() > max {
+                return Err(PaymentError::TooManyDenoms {
+                    max,
+                    sent: funds.len(),
+                });
+            }
+        }
+        for min in &self.min_amounts {
+            match find_amount(&funds, &min.denom) {
+                Some(sent) if sent < min.amount => {
+                    return Err(PaymentError::BelowMinimum {
+                        denom: min.denom.clone(),
+                        min: min.amount,
+                        sent,
+                    })
+                }
+                _ => {}
+            }
+        }
+        Ok(funds.into_iter().cloned().collect())
+    }
+}
+
+// the non-zero funds sent, erroring if a denom was sent twice
+fn sent_funds(info: &MessageInfo) -> Result<Vec<&Coin>, PaymentError> {
+    let mut denoms = BTreeSet::new();
+    let mut funds = Vec::with_capacity(info.funds.len());
+    for coin in &info.funds {
+        if !denoms.insert(coin.denom.as_str()) {
+            return Err(PaymentError::DuplicateDenom(coin.denom.clone()));
+        }
+        if !coin.amount.is_zero() {
+            funds.push(coin);
+        }
+    }
+    Ok(funds)
+}
+
+// errors on the first denom that was sent but is not in the list
+fn check_denoms(funds: &[&Coin], denoms: &[&str]) -> Result<(), PaymentError> {
+    match funds.iter().find(|c| !denoms.contains(&c.denom.as_str())) {
+        Some(wrong) => Err(PaymentError::ExtraDenom(wrong.denom.clone())),
+        None => Ok(()),
+    }
+}
+
+fn find_amount(funds: &[&Coin], denom: &str) -> Option<Uint128> {
+    funds.iter().find(|c| c.denom == denom).map(|c| c.amount)
+}
+
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum PaymentError {
     #[error("Must send reserve token '{0}'")]
@@ -60,6 +316,9 @@ pub enum PaymentError {
     #[error("Received unsupported denom '{0}'")]
     ExtraDenom(String),
 
+    #[error("Must send cw20 token '{0}'")]
+    MissingCw20(String),
+
     #[error("Sent more than one denomination")]
     MultipleDenoms {},
 
@@ -68,13 +327,47 @@ pub enum PaymentError {
 
     #[error("This message does no accept funds")]
     NonPayable {},
+
+    #[error("Denomination '{0}' was sent more than once")]
+    DuplicateDenom(String),
+
+    #[error("Sent {sent} denominations, but at most {max} are accepted")]
+    TooManyDenoms { max: usize, sent: usize },
+
+    #[error("Must send {expected}{denom}, but sent {sent}{denom}")]
+    WrongAmount {
+        denom: String,
+        expected: Uint128,
+        sent: Uint128,
+    },
+
+    #[error("Must send at least {min}{denom}, but sent {sent}{denom}")]
+    BelowMinimum {
+        denom: String,
+        min: Uint128,
+        sent: Uint128,
+    },
+
+    #[error("Insufficient funds: required {required}{denom}, but sent {sent}{denom}")]
+    InsufficientFunds {
+        denom: String,
+        required: Uint128,
+        sent: Uint128,
+    },
+
+    #[error("Overpaid: required {required}{denom}, but sent {sent}{denom}")]
+    Overpaid {
+        denom: String,
+        required: Uint128,
+        sent: Uint128,
+    },
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use cosmwasm_std::testing::mock_info;
-    use cosmwasm_std::{coin, coins};
+    use cosmwasm_std::{coin, coins, Addr};
 
     const SENDER: &str = "sender";
 
@@ -133,4 +426,328 @@ mod test {
         let err = must_pay(&mixed_payment, atom).unwrap_err();
         assert_eq!(err, PaymentError::MultipleDenoms {});
     }
+
+    #[test]
+    fn must_pay_many_works() {
+        let atom: &str = "uatom";
+        let osmo: &str = "uosmo";
+        let both = mock_info(SENDER, &[coin(50, osmo), coin(100, atom)]);
+        let only_atom = mock_info(SENDER, &coins(100, atom));
+        let with_zero = mock_info(SENDER, &[coin(100, atom), coin(0, osmo)]);
+        let extra = mock_info(SENDER, &[coin(100, atom), coin(50, osmo), coin(1, "wei")]);
+        let duplicate = mock_info(SENDER, &[coin(100, atom), coin(50, atom)]);
+
+        // amounts are in the requested order
+        let res = must_pay_many(&both, &[atom, osmo]).unwrap();
+        assert_eq!(res, vec![Uint128::new(100), Uint128::new(50)]);
+        let res = must_pay_many(&both, &[osmo, atom]).unwrap();
+        assert_eq!(res, vec![Uint128::new(50), Uint128::new(100)]);
+
+        let err = must_pay_many(&only_atom, &[atom, osmo]).unwrap_err();
+        assert_eq!(err, PaymentError::MissingDenom(osmo.to_string()));
+        let err = must_pay_many(&with_zero, &[atom, osmo]).unwrap_err();
+        assert_eq!(err, PaymentError::MissingDenom(osmo.to_string()));
+        let err = must_pay_many(&mock_info(SENDER, &[]), &[atom, osmo]).unwrap_err();
+        assert_eq!(err, PaymentError::NoFunds {});
+        let err = must_pay_many(&extra, &[atom, osmo]).unwrap_err();
+        assert_eq!(err, PaymentError::ExtraDenom("wei".to_string()));
+        let err = must_pay_many(&both, &[atom]).unwrap_err();
+        assert_eq!(err, PaymentError::ExtraDenom(osmo.to_string()));
+        let err = must_pay_many(&duplicate, &[atom]).unwrap_err();
+        assert_eq!(err, PaymentError::DuplicateDenom(atom.to_string()));
+    }
+
+    #[test]
+    fn may_pay_many_works() {
+        let atom: &str = "uatom";
+        let osmo: &str = "uosmo";
+        let no_payment = mock_info(SENDER, &[]);
+        let only_osmo = mock_info(SENDER, &coins(50, osmo));
+        let both = mock_info(SENDER, &[coin(50, osmo), coin(100, atom)]);
+        let eth_payment = mock_info(SENDER, &[coin(50, osmo), coin(100, "wei")]);
+
+        let res = may_pay_many(&no_payment, &[atom, osmo]).unwrap();
+        assert_eq!(res, vec![Uint128::zero(), Uint128::zero()]);
+        let res = may_pay_many(&only_osmo, &[atom, osmo]).unwrap();
+        assert_eq!(res, vec![Uint128::zero(), Uint128::new(50)]);
+        let res = may_pay_many(&both, &[atom, osmo]).unwrap();
+        assert_eq!(res, vec![Uint128::new(100), Uint128::new(50)]);
+
+        let err = may_pay_many(&eth_payment, &[atom, osmo]).unwrap_err();
+        assert_eq!(err, PaymentError::ExtraDenom("wei".to_string()));
+    }
+
+    #[test]
+    fn must_pay_exact_works() {
+        let atom: &str = "uatom";
+        let osmo: &str = "uosmo";
+        let price = [coin(100, atom), coin(50, osmo)];
+
+        let exact = mock_info(SENDER, &[coin(50, osmo), coin(100, atom)]);
+        must_pay_exact(&exact, &price).unwrap();
+
+        let too_little = mock_info(SENDER, &[coin(99, atom), coin(50, osmo)]);
+        let err = must_pay_exact(&too_little, &price).unwrap_err();
+        assert_eq!(
+            err,
+            PaymentError::WrongAmount {
+                denom: atom.to_string(),
+                expected: Uint128::new(100),
+                sent: Uint128::new(99)
+            }
+        );
+        let too_much = mock_info(SENDER, &[coin(100, atom), coin(51, osmo)]);
+        let err = must_pay_exact(&too_much, &price).unwrap_err();
+        assert_eq!(
+            err,
+            PaymentError::WrongAmount {
+                denom: osmo.to_string(),
+                expected: Uint128::new(50),
+                sent: Uint128::new(51)
+            }
+        );
+
+        let missing = mock_info(SENDER, &coins(100, atom));
+        let err = must_pay_exact(&missing, &price).unwrap_err();
+        assert_eq!(err, PaymentError::MissingDenom(osmo.to_string()));
+
+        let extra = mock_info(SENDER, &[coin(100, atom), coin(50, osmo), coin(1, "wei")]);
+        let err = must_pay_exact(&extra, &price).unwrap_err();
+        assert_eq!(err, PaymentError::ExtraDenom("wei".to_string()));
+
+        // a zero amount must not be sent
+        let free = [coin(100, atom), coin(0, osmo)];
+        must_pay_exact(&missing, &free).unwrap();
+        let err = must_pay_exact(&exact, &free).unwrap_err();
+        assert_eq!(
+            err,
+            PaymentError::WrongAmount {
+                denom: osmo.to_string(),
+                expected: Uint128::zero(),
+                sent: Uint128::new(50)
+            }
+        );
+
+        // nothing expected
+        must_pay_exact(&mock_info(SENDER, &[]), &[]).unwrap();
+        let err = must_pay_exact(&missing, &[]).unwrap_err();
+        assert_eq!(err, PaymentError::ExtraDenom(atom.to_string()));
+    }
+
+    #[test]
+    fn payment_policy_works() {
+        let atom: &str = "uatom";
+        let osmo: &str = "uosmo";
+        let no_payment = mock_info(SENDER, &[]);
+        let atom_payment = mock_info(SENDER, &coins(100, atom));
+        let both = mock_info(SENDER, &[coin(100, atom), coin(0, "wei"), coin(50, osmo)]);
+        let eth_payment = mock_info(SENDER, &coins(100, "wei"));
+
+        // accepts anything but nothing
+        let policy = PaymentPolicy::new();
+        assert_eq!(
+            policy.check(&both).unwrap(),
+            vec![coin(100, atom), coin(50, osmo)]
+        );
+        let err = policy.check(&no_payment).unwrap_err();
+        assert_eq!(err, PaymentError::NoFunds {});
+        let policy = policy.allow_zero(true);
+        assert_eq!(policy.check(&no_payment).unwrap(), vec![]);
+
+        // whitelist
+        let policy = PaymentPolicy::new().allow(atom).allow(osmo);
+        policy.check(&atom_payment).unwrap();
+        policy.check(&both).unwrap();
+        let err = policy.check(&eth_payment).unwrap_err();
+        assert_eq!(err, PaymentError::ExtraDenom("wei".to_string()));
+
+        // max denoms
+        let policy = PaymentPolicy::new().allow(atom).allow(osmo).max_denoms(1);
+        policy.check(&atom_payment).unwrap();
+        let err = policy.check(&both).unwrap_err();
+        assert_eq!(err, PaymentError::TooManyDenoms { max: 1, sent: 2 });
+
+        // min amounts only apply to the denoms sent
+        let policy = PaymentPolicy::new()
+            .min_amount(coin(200, atom))
+            .min_amount(coin(10, osmo));
+        let err = policy.check(&atom_payment).unwrap_err();
+        assert_eq!(
+            err,
+            PaymentError::BelowMinimum {
+                denom: atom.to_string(),
+                min: Uint128::new(200),
+                sent: Uint128::new(100)
+            }
+        );
+        policy.check(&mock_info(SENDER, &coins(10, osmo))).unwrap();
+        // the last minimum for a denom wins
+        let policy = policy.min_amount(coin(100, atom));
+        policy.check(&atom_payment).unwrap();
+
+        let duplicate = mock_info(SENDER, &[coin(100, atom), coin(50, atom)]);
+        let err = PaymentPolicy::new().check(&duplicate).unwrap_err();
+        assert_eq!(err, PaymentError::DuplicateDenom(atom.to_string()));
+    }
+
+    #[test]
+    fn must_pay_at_least_works() {
+        let atom: &str = "uatom";
+        let price = coin(100, atom);
+
+        let res = must_pay_at_least(&mock_info(SENDER, &coins(100, atom)), &price).unwrap();
+        assert_eq!(res, Uint128::new(100));
+        let res = must_pay_at_least(&mock_info(SENDER, &coins(150, atom)), &price).unwrap();
+        assert_eq!(res, Uint128::new(150));
+
+        let err = must_pay_at_least(&mock_info(SENDER, &coins(99, atom)), &price).unwrap_err();
+        assert_eq!(
+            err,
+            PaymentError::InsufficientFunds {
+                denom: atom.to_string(),
+                required: Uint128::new(100),
+                sent: Uint128::new(99)
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Insufficient funds: required 100uatom, but sent 99uatom"
+        );
+
+        // the must_pay errors are kept
+        let err = must_pay_at_least(&mock_info(SENDER, &[]), &price).unwrap_err();
+        assert_eq!(err, PaymentError::NoFunds {});
+        let err = must_pay_at_least(&mock_info(SENDER, &coins(100, "wei")), &price).unwrap_err();
+        assert_eq!(err, PaymentError::MissingDenom(atom.to_string()));
+        let mixed_payment = mock_info(SENDER, &[coin(100, atom), coin(1, "wei")]);
+        let err = must_pay_at_least(&mixed_payment, &price).unwrap_err();
+        assert_eq!(err, PaymentError::MultipleDenoms {});
+    }
+
+    #[test]
+    fn must_pay_exactly_works() {
+        let atom: &str = "uatom";
+        let price = coin(100, atom);
+
+        must_pay_exactly(&mock_info(SENDER, &coins(100, atom)), &price).unwrap();
+
+        let err = must_pay_exactly(&mock_info(SENDER, &coins(99, atom)), &price).unwrap_err();
+        assert_eq!(
+            err,
+            PaymentError::InsufficientFunds {
+                denom: atom.to_string(),
+                required: Uint128::new(100),
+                sent: Uint128::new(99)
+            }
+        );
+        let err = must_pay_exactly(&mock_info(SENDER, &coins(101, atom)), &price).unwrap_err();
+        assert_eq!(
+            err,
+            PaymentError::Overpaid {
+                denom: atom.to_string(),
+                required: Uint128::new(100),
+                sent: Uint128::new(101)
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Overpaid: required 100uatom, but sent 101uatom"
+        );
+    }
+
+    #[test]
+    fn pay_with_change_works() {
+        let atom: &str = "uatom";
+        let price = coin(100, atom);
+
+        let res = pay_with_change(&mock_info(SENDER, &coins(100, atom)), &price).unwrap();
+        assert_eq!(res, None);
+
+        let res = pay_with_change(&mock_info(SENDER, &coins(175, atom)), &price).unwrap();
+        assert_eq!(
+            res,
+            Some(BankMsg::Send {
+                to_address: SENDER.to_string(),
+                amount: coins(75, atom)
+            })
+        );
+
+        let err = pay_with_change(&mock_info(SENDER, &coins(50, atom)), &price).unwrap_err();
+        assert_eq!(
+            err,
+            PaymentError::InsufficientFunds {
+                denom: atom.to_string(),
+                required: Uint128::new(100),
+                sent: Uint128::new(50)
+            }
+        );
+    }
+
+    #[test]
+    fn must_pay_asset_works() {
+        let atom: &str = "uatom";
+        let token = Addr::unchecked("token");
+        let native = AssetInfo::Native(atom.to_string());
+        let cw20 = AssetInfo::Cw20(token.clone());
+
+        let atom_info = mock_info(SENDER, &coins(100, atom));
+        let atom_payment = AssetPayment::Native(&atom_info);
+        let hook_info = mock_info("token", &[]);
+        let cw20_payment = AssetPayment::Cw20 {
+            info: &hook_info,
+            sender: "payer",
+            amount: Uint128::new(50),
+        };
+
+        assert_eq!(atom_payment.payer(), SENDER);
+        assert_eq!(cw20_payment.payer(), "payer");
+        assert_eq!(
+            one_asset(&atom_payment).unwrap(),
+            Asset::native(100u128, atom)
+        );
+        assert_eq!(
+            one_asset(&cw20_payment).unwrap(),
+            Asset::cw20(50u128, token.clone())
+        );
+
+        let res = must_pay_asset(&atom_payment, &native).unwrap();
+        assert_eq!(res, Uint128::new(100));
+        let res = must_pay_asset(&cw20_payment, &cw20).unwrap();
+        assert_eq!(res, Uint128::new(50));
+
+        // wrong kind of token
+        let err = must_pay_asset(&atom_payment, &cw20).unwrap_err();
+        assert_eq!(err, PaymentError::MissingCw20("token".to_string()));
+        let err = must_pay_asset(&cw20_payment, &native).unwrap_err();
+        assert_eq!(err, PaymentError::MissingDenom(atom.to_string()));
+        // wrong token
+        let other = AssetInfo::Cw20(Addr::unchecked("other"));
+        let err = must_pay_asset(&cw20_payment, &other).unwrap_err();
+        assert_eq!(err, PaymentError::MissingCw20("other".to_string()));
+
+        // nothing sent
+        let no_info = mock_info(SENDER, &[]);
+        let err = must_pay_asset(&AssetPayment::Native(&no_info), &native).unwrap_err();
+        assert_eq!(err, PaymentError::NoFunds {});
+        let zero_cw20 = AssetPayment::Cw20 {
+            info: &hook_info,
+            sender: "payer",
+            amount: Uint128::zero(),
+        };
+        let err = must_pay_asset(&zero_cw20, &cw20).unwrap_err();
+        assert_eq!(err, PaymentError::NoFunds {});
+
+        // too much sent
+        let mixed_info = mock_info(SENDER, &[coin(50, atom), coin(120, "wei")]);
+        let err = must_pay_asset(&AssetPayment::Native(&mixed_info), &native).unwrap_err();
+        assert_eq!(err, PaymentError::MultipleDenoms {});
+        let hook_with_funds = mock_info("token", &coins(1, atom));
+        let cw20_and_native = AssetPayment::Cw20 {
+            info: &hook_with_funds,
+            sender: "payer",
+            amount: Uint128::new(50),
+        };
+        let err = must_pay_asset(&cw20_and_native, &cw20).unwrap_err();
+        assert_eq!(err, PaymentError::ExtraDenom(atom.to_string()));
+    }
 }
@@ -0,0 +1,383 @@
+use crate::{Duration, Expiration, Scheduled};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{BlockInfo, StdError, StdResult, Timestamp};
+
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+/// RecurringSchedule represents an event that happens every `interval`, starting at `start`.
+/// If an `end` is given, there are no more occurrences once it is expired.
+///
+/// `start`, `interval` and `end` must all be either height- or time-based
+/// (`end` may also be `Never`), see `validate`.
+#[cw_serde]
+pub struct RecurringSchedule {
+    /// The first occurrence
+    pub start: Scheduled,
+    /// The time between two occurrences
+    pub interval: Duration,
+    /// Occurrences at or after this point are dropped
+    pub end: Option<Expiration>,
+}
+
+impl RecurringSchedule {
+    /// returns an error if start, interval and end are not of the same kind, or the
+    /// interval is zero
+    pub fn validate(&self) -> StdResult<()> {
+        self.params().map(|_| ())
+    }
+
+    /// Returns the first occurrence that is not triggered yet at the given block,
+    /// or None if there are no more occurrences.
+    pub fn next_after(&self, block: &BlockInfo) -> StdResult<Option<Scheduled>> {
+        let params = self.params()?;
+        let next = params.triggered(params.position(block));
+        if next >= params.count() {
+            return Ok(None);
+        }
+        // occurrences that do not fit into a u64 can never be reached
+        let position = params.start + next as u128 * params.step;
+        Ok(u64::try_from(position)
+            .ok()
+            .map(|position| match self.interval {
+                Duration::Height(_) => Scheduled::AtHeight(position),
+                Duration::Time(_) => Scheduled::AtTime(Timestamp::from_nanos(position)),
+            }))
+    }
+
+    /// Returns how many occurrences were triggered after block `from`, up to and including
+    /// block `to`. This is zero if `to` is before `from`.
+    pub fn occurrences_between(&self, from: &BlockInfo, to: &BlockInfo) -> StdResult<u64> {
+        let params = self.params()?;
+        let before = params.triggered(params.position(from));
+        let after = params.triggered(params.position(to));
+        Ok(after.saturating_sub(before))
+    }
+
+    /// Returns how many occurrences were triggered since the last run of a contract,
+    /// up to and including the current block. If it never ran, this includes all triggered
+    /// occurrences.
+    pub fn missed_count(&self, last_run: Option<&BlockInfo>, block: &BlockInfo) -> StdResult<u64> {
+        match last_run {
+            Some(last_run) => self.occurrences_between(last_run, block),
+            None => {
+                let params = self.params()?;
+                Ok(params.triggered(params.position(block)))
+            }
+        }
+    }
+
+    fn params(&self) -> StdResult<Params> {
+        let (start, step) = match (self.start, self.interval) {
+            (Scheduled::AtHeight(height), Duration::Height(interval)) => {
+                (height as u128, interval as u128)
+            }
+            (Scheduled::AtTime(time), Duration::Time(interval)) => {
+                (time.nanos() as u128, interval as u128 * NANOS_PER_SECOND)
+            }
+            _ => {
+                return Err(StdError::generic_err(
+                    "Recurring schedule start and interval must both be height or time",
+                ))
+            }
+        };
+        if step == 0 {
+            return Err(StdError::generic_err(
+                "Recurring schedule interval cannot be zero",
+            ));
+        }
+        let end = match (self.end, self.interval) {
+            (None, _) | (Some(Expiration::Never {}), _) => None,
+            (Some(Expiration::AtHeight(height)), Duration::Height(_)) => Some(height as u128),
+            (Some(Expiration::AtTime(time)), Duration::Time(_)) => Some(time.nanos() as u128),
+            _ => {
+                return Err(StdError::generic_err(
+                    "Recurring schedule end must be of the same kind as the interval",
+                ))
+            }
+        };
+        Ok(Params {
+            height_based: matches!(self.interval, Duration::Height(_)),
+            start,
+            step,
+            end,
+        })
+    }
+}
+
+/// The schedule on a single axis, of heights or nanoseconds
+struct Params {
+    height_based: bool,
+    start: u128,
+    step: u128,
+    end: Option<u128>,
+}
+
+impl Params {
+    fn position(&self, block: &BlockInfo) -> u128 {
+        if self.height_based {
+            block.height as u128
+        } else {
+            block.time.nanos() as u128
+        }
+    }
+
+    /// the total number of occurrences before the end (u64::MAX if unlimited)
+    fn count(&self) -> u64 {
+        match self.end {
+            Some(end) if end <= self.start => 0,
+            Some(end) => u64::try_from((end - self.start).div_ceil(self.step)).unwrap_or(u64::MAX),
+            None => u64::MAX,
+        }
+    }
+
+    /// the number of occurrences triggered at the given position
+    fn triggered(&self, position: u128) -> u64 {
+        if position < self.start {
+            return 0;
+        }
+        let triggered = u64::try_from((position - self.start) / self.step + 1).unwrap_or(u64::MAX);
+        triggered.min(self.count())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{DAY, HOUR};
+
+    fn block(height: u64, time: u64) -> BlockInfo {
+        BlockInfo {
+            height,
+            time: Timestamp::from_seconds(time),
+            chain_id: "foo".to_string(),
+        }
+    }
+
+    fn every_ten_blocks(end: Option<Expiration>) -> RecurringSchedule {
+        RecurringSchedule {
+            start: Scheduled::AtHeight(100),
+            interval: Duration::Height(10),
+            end,
+        }
+    }
+
+    #[test]
+    fn validate_schedule() {
+        every_ten_blocks(None).validate().unwrap();
+        every_ten_blocks(Some(Expiration::Never {}))
+            .validate()
+            .unwrap();
+        every_ten_blocks(Some(Expiration::AtHeight(200)))
+            .validate()
+            .unwrap();
+
+        // kinds must match
+        every_ten_blocks(Some(Expiration::AtTime(Timestamp::from_seconds(200))))
+            .validate()
+            .unwrap_err();
+        RecurringSchedule {
+            start: Scheduled::AtHeight(100),
+            interval: DAY,
+            end: None,
+        }
+        .validate()
+        .unwrap_err();
+        RecurringSchedule {
+            start: Scheduled::AtHeightOrTime {
+                height: 100,
+                time: Timestamp::from_seconds(100),
+            },
+            interval: Duration::Height(10),
+            end: None,
+        }
+        .validate()
+        .unwrap_err();
+
+        // interval must not be zero
+        RecurringSchedule {
+            start: Scheduled::AtHeight(100),
+            interval: Duration::Height(0),
+            end: None,
+        }
+        .validate()
+        .unwrap_err();
+
+        // all queries validate
+        let invalid = RecurringSchedule {
+            start: Scheduled::AtHeight(100),
+            interval: DAY,
+            end: None,
+        };
+        invalid.next_after(&block(1, 1)).unwrap_err();
+        invalid
+            .occurrences_between(&block(1, 1), &block(2, 2))
+            .unwrap_err();
+        invalid.missed_count(None, &block(2, 2)).unwrap_err();
+    }
+
+    #[test]
+    fn next_after_works() {
+        let schedule = every_ten_blocks(Some(Expiration::AtHeight(130)));
+
+        assert_eq!(
+            schedule.next_after(&block(0, 0)).unwrap(),
+            Some(Scheduled::AtHeight(100))
+        );
+        assert_eq!(
+            schedule.next_after(&block(99, 0)).unwrap(),
+            Some(Scheduled::AtHeight(100))
+        );
+        // occurrences are triggered at their height
+        assert_eq!(
+            schedule.next_after(&block(100, 0)).unwrap(),
+            Some(Scheduled::AtHeight(110))
+        );
+        assert_eq!(
+            schedule.next_after(&block(119, 0)).unwrap(),
+            Some(Scheduled::AtHeight(120))
+        );
+        // there is no occurrence at the end
+        assert_eq!(schedule.next_after(&block(120, 0)).unwrap(), None);
+        assert_eq!(schedule.next_after(&block(500, 0)).unwrap(), None);
+
+        // unlimited
+        let schedule = every_ten_blocks(None);
+        assert_eq!(
+            schedule.next_after(&block(500, 0)).unwrap(),
+            Some(Scheduled::AtHeight(510))
+        );
+        assert_eq!(schedule.next_after(&block(u64::MAX - 5, 0)).unwrap(), None);
+
+        // time based
+        let schedule = RecurringSchedule {
+            start: Scheduled::AtTime(Timestamp::from_seconds(1000)),
+            interval: HOUR,
+            end: Some(Expiration::AtTime(Timestamp::from_seconds(1000 + 3 * 3600))),
+        };
+        assert_eq!(
+            schedule.next_after(&block(0, 0)).unwrap(),
+            Some(Scheduled::AtTime(Timestamp::from_seconds(1000)))
+        );
+        assert_eq!(
+            schedule.next_after(&block(0, 1000 + 3600)).unwrap(),
+            Some(Scheduled::AtTime(Timestamp::from_seconds(1000 + 2 * 3600)))
+        );
+        assert_eq!(
+            schedule.next_after(&block(0, 1000 + 2 * 3600)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn occurrences_between_works() {
+        let schedule = every_ten_blocks(Some(Expiration::AtHeight(131)));
+
+        // occurrences at 100, 110, 120 and 130
+        assert_eq!(
+            schedule
+                .occurrences_between(&block(0, 0), &block(99, 0))
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            schedule
+                .occurrences_between(&block(0, 0), &block(100, 0))
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            schedule
+                .occurrences_between(&block(100, 0), &block(119, 0))
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            schedule
+                .occurrences_between(&block(100, 0), &block(120, 0))
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            schedule
+                .occurrences_between(&block(0, 0), &block(1000, 0))
+                .unwrap(),
+            4
+        );
+        assert_eq!(
+            schedule
+                .occurrences_between(&block(130, 0), &block(1000, 0))
+                .unwrap(),
+            0
+        );
+        // reversed
+        assert_eq!(
+            schedule
+                .occurrences_between(&block(120, 0), &block(100, 0))
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn missed_count_works() {
+        let schedule = RecurringSchedule {
+            start: Scheduled::AtTime(Timestamp::from_seconds(1000)),
+            interval: DAY,
+            end: None,
+        };
+
+        assert_eq!(schedule.missed_count(None, &block(0, 999)).unwrap(), 0);
+        assert_eq!(schedule.missed_count(None, &block(0, 1000)).unwrap(), 1);
+        assert_eq!(
+            schedule
+                .missed_count(None, &block(0, 1000 + 3 * 86400))
+                .unwrap(),
+            4
+        );
+
+        // ran at the first occurrence, then missed 2 days and a bit
+        let last_run = block(10, 1000);
+        assert_eq!(
+            schedule
+                .missed_count(Some(&last_run), &block(20, 1000 + 2 * 86400 + 5))
+                .unwrap(),
+            2
+        );
+        // nothing missed within the same period
+        assert_eq!(
+            schedule
+                .missed_count(Some(&last_run), &block(20, 1000 + 86399))
+                .unwrap(),
+            0
+        );
+
+        // sub-second start times are respected
+        let schedule = RecurringSchedule {
+            start: Scheduled::AtTime(Timestamp::from_nanos(1_500_000_000)),
+            interval: Duration::Time(1),
+            end: Some(Expiration::AtTime(Timestamp::from_seconds(4))),
+        };
+        // occurrences at 1.5s, 2.5s and 3.5s
+        assert_eq!(schedule.missed_count(None, &block(0, 2)).unwrap(), 1);
+        assert_eq!(schedule.missed_count(None, &block(0, 100)).unwrap(), 3);
+    }
+
+    #[test]
+    fn counts_match_next_after() {
+        let schedule = every_ten_blocks(Some(Expiration::AtHeight(175)));
+        let mut last = block(0, 0);
+        let mut total = 0;
+        for height in 0..250 {
+            let current = block(height, 0);
+            let triggered = schedule.occurrences_between(&last, &current).unwrap();
+            match schedule.next_after(&last).unwrap() {
+                Some(next) => assert_eq!(triggered > 0, next.is_triggered(&current)),
+                None => assert_eq!(triggered, 0),
+            }
+            total += triggered;
+            last = current;
+        }
+        assert_eq!(total, 8);
+        assert_eq!(schedule.missed_count(None, &block(1000, 0)).unwrap(), total);
+    }
+}
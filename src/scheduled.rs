@@ -1,4 +1,5 @@
-use crate::Duration;
+use crate::expiration::{checked_plus_height, checked_plus_seconds};
+use crate::{BlockTimeEstimate, Duration, Expiration};
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{BlockInfo, StdError, StdResult, Timestamp};
 use std::cmp::Ordering;
@@ -15,6 +16,12 @@ pub enum Scheduled {
     AtHeight(u64),
     /// AtTime will schedule when `env.block.time` >= time
     AtTime(Timestamp),
+    /// AtHeightOrTime will schedule when `env.block.height` >= height or
+    /// `env.block.time` >= time, whichever comes first
+    AtHeightOrTime { height: u64, time: Timestamp },
+    /// AtHeightAndTime will schedule when `env.block.height` >= height and
+    /// `env.block.time` >= time, whichever comes last
+    AtHeightAndTime { height: u64, time: Timestamp },
 }
 
 impl fmt::Display for Scheduled {
@@ -22,6 +29,12 @@ impl fmt::Display for Scheduled {
         match self {
             Scheduled::AtHeight(height) => write!(f, "scheduled height: {}", height),
             Scheduled::AtTime(time) => write!(f, "scheduled time: {}", time),
+            Scheduled::AtHeightOrTime { height, time } => {
+                write!(f, "scheduled height: {} or time: {}", height, time)
+            }
+            Scheduled::AtHeightAndTime { height, time } => {
+                write!(f, "scheduled height: {} and time: {}", height, time)
+            }
         }
     }
 }
@@ -32,6 +45,33 @@ impl Scheduled {
         match self {
             Scheduled::AtHeight(height) => block.height >= *height,
             Scheduled::AtTime(time) => block.time >= *time,
+            Scheduled::AtHeightOrTime { height, time } => {
+                block.height >= *height || block.time >= *time
+            }
+            Scheduled::AtHeightAndTime { height, time } => {
+                block.height >= *height && block.time >= *time
+            }
+        }
+    }
+
+    /// Compares two schedules of any kind, by estimating when they will trigger.
+    /// See `Expiration::compare_with`.
+    pub fn compare_with(&self, other: &Scheduled, estimate: &BlockTimeEstimate) -> Ordering {
+        self.to_expiration()
+            .compare_with(&other.to_expiration(), estimate)
+    }
+
+    /// the expiration that is hit at the same point as this schedule
+    fn to_expiration(self) -> Expiration {
+        match self {
+            Scheduled::AtHeight(height) => Expiration::AtHeight(height),
+            Scheduled::AtTime(time) => Expiration::AtTime(time),
+            Scheduled::AtHeightOrTime { height, time } => {
+                Expiration::AtHeightOrTime { height, time }
+            }
+            Scheduled::AtHeightAndTime { height, time } => {
+                Expiration::AtHeightAndTime { height, time }
+            }
         }
     }
 }
@@ -42,28 +82,52 @@ impl Add<Duration> for Scheduled {
     fn add(self, duration: Duration) -> StdResult<Scheduled> {
         match (self, duration) {
             (Scheduled::AtTime(t), Duration::Time(delta)) => {
-                Ok(Scheduled::AtTime(t.plus_seconds(delta)))
+                Ok(Scheduled::AtTime(checked_plus_seconds(t, delta)?))
+            }
+            (Scheduled::AtHeight(h), Duration::Height(delta)) => {
+                Ok(Scheduled::AtHeight(checked_plus_height(h, delta)?))
+            }
+            // compound schedules only move the part matching the duration
+            (Scheduled::AtHeightOrTime { height, time }, Duration::Height(delta)) => {
+                Ok(Scheduled::AtHeightOrTime {
+                    height: checked_plus_height(height, delta)?,
+                    time,
+                })
+            }
+            (Scheduled::AtHeightOrTime { height, time }, Duration::Time(delta)) => {
+                Ok(Scheduled::AtHeightOrTime {
+                    height,
+                    time: checked_plus_seconds(time, delta)?,
+                })
+            }
+            (Scheduled::AtHeightAndTime { height, time }, Duration::Height(delta)) => {
+                Ok(Scheduled::AtHeightAndTime {
+                    height: checked_plus_height(height, delta)?,
+                    time,
+                })
+            }
+            (Scheduled::AtHeightAndTime { height, time }, Duration::Time(delta)) => {
+                Ok(Scheduled::AtHeightAndTime {
+                    height,
+                    time: checked_plus_seconds(time, delta)?,
+                })
             }
-            (Scheduled::AtHeight(h), Duration::Height(delta)) => Ok(Scheduled::AtHeight(h + delta)),
             _ => Err(StdError::generic_err("Cannot add height and time")),
         }
     }
 }
 
+/// Schedules are ordered like the matching `Expiration`s
 impl PartialOrd for Scheduled {
     fn partial_cmp(&self, other: &Scheduled) -> Option<Ordering> {
-        match (self, other) {
-            // compare if both height or both time
-            (Scheduled::AtHeight(h1), Scheduled::AtHeight(h2)) => Some(h1.cmp(h2)),
-            (Scheduled::AtTime(t1), Scheduled::AtTime(t2)) => Some(t1.cmp(t2)),
-            _ => None,
-        }
+        self.to_expiration().partial_cmp(&other.to_expiration())
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use cosmwasm_std::Decimal;
 
     #[test]
     fn compare_schedules() {
@@ -95,6 +159,68 @@ mod test {
         assert!(!(Scheduled::AtTime(Timestamp::from_seconds(1000)) == Scheduled::AtHeight(230)));
     }
 
+    #[test]
+    fn compound_schedules() {
+        let block = |height: u64, time: u64| BlockInfo {
+            height,
+            time: Timestamp::from_seconds(time),
+            chain_id: "foo".to_string(),
+        };
+        let time = Timestamp::from_seconds(1000);
+        let or = Scheduled::AtHeightOrTime { height: 100, time };
+        let and = Scheduled::AtHeightAndTime { height: 100, time };
+
+        assert!(!or.is_triggered(&block(99, 999)));
+        assert!(or.is_triggered(&block(100, 999)));
+        assert!(or.is_triggered(&block(99, 1000)));
+        assert!(!and.is_triggered(&block(100, 999)));
+        assert!(!and.is_triggered(&block(99, 1000)));
+        assert!(and.is_triggered(&block(100, 1000)));
+
+        assert!(or < and);
+        assert!(or < Scheduled::AtHeight(100));
+        assert!(and > Scheduled::AtTime(time));
+        assert_eq!(
+            or.partial_cmp(&Scheduled::AtTime(Timestamp::from_seconds(999))),
+            None
+        );
+
+        assert_eq!(
+            or.to_string(),
+            "scheduled height: 100 or time: 1000.000000000"
+        );
+        assert_eq!(
+            and.to_string(),
+            "scheduled height: 100 and time: 1000.000000000"
+        );
+
+        let later = (and + Duration::Height(5)).unwrap();
+        assert_eq!(later, Scheduled::AtHeightAndTime { height: 105, time });
+        let later = (or + Duration::Time(5)).unwrap();
+        assert_eq!(
+            later,
+            Scheduled::AtHeightOrTime {
+                height: 100,
+                time: Timestamp::from_seconds(1005)
+            }
+        );
+    }
+
+    #[test]
+    fn compare_schedules_with_estimate() {
+        let estimate = BlockTimeEstimate {
+            height: 1000,
+            time: Timestamp::from_seconds(10000),
+            seconds_per_block: Decimal::percent(500),
+        };
+        let height = Scheduled::AtHeight(1002);
+        let time = Scheduled::AtTime(Timestamp::from_seconds(10009));
+        assert_eq!(height.partial_cmp(&time), None);
+        assert_eq!(height.compare_with(&time, &estimate), Ordering::Greater);
+        assert_eq!(time.compare_with(&height, &estimate), Ordering::Less);
+        assert_eq!(height.compare_with(&height, &estimate), Ordering::Equal);
+    }
+
     #[test]
     fn schedule_addition() {
         // height
@@ -111,5 +237,12 @@ mod test {
         // mismatched
         let end = Scheduled::AtHeight(12345) + Duration::Time(1500);
         end.unwrap_err();
+
+        // overflow
+        let err = (Scheduled::AtHeight(12345) + Duration::Height(u64::MAX)).unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        let err =
+            (Scheduled::AtTime(Timestamp::from_seconds(1)) + Duration::Time(u64::MAX)).unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
     }
 }
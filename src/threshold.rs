@@ -1,5 +1,5 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Decimal, StdError};
+use cosmwasm_std::{Decimal, OverflowError, OverflowOperation, StdError, Uint128, Uint256};
 use thiserror::Error;
 
 /// This defines the different ways tallies can happen.
@@ -24,17 +24,69 @@ pub enum Threshold {
     /// for the vote to be considered at all.
     /// See `ThresholdResponse.ThresholdQuorum` in the cw3 spec for details.
     ThresholdQuorum { threshold: Decimal, quorum: Decimal },
+
+    /// Selects a different threshold depending on the kind of the proposal, e.g. to require
+    /// a higher threshold for treasury moves than for routine proposals.
+    /// Proposals without a kind use the `default` threshold.
+    /// See `ThresholdResponse.Tiered` for details.
+    Tiered {
+        default: Box<Threshold>,
+        tiers: Vec<ThresholdTier>,
+    },
+
+    /// Applies another threshold, but rejects the proposal outright as soon as a `veto`
+    /// percentage of the total weight has cast Veto votes, regardless of the Yes votes.
+    /// See `ThresholdResponse.WithVeto` for details.
+    WithVeto {
+        threshold: Box<Threshold>,
+        veto: Decimal,
+    },
+}
+
+/// The threshold used for all proposals of the given `kind` in `Threshold::Tiered`
+#[cw_serde]
+pub struct ThresholdTier {
+    pub kind: String,
+    pub threshold: Threshold,
 }
 
 impl Threshold {
     /// returns error if this is an unreachable value,
     /// given a total weight of all members in the group
     pub fn validate(&self, total_weight: u64) -> Result<(), ThresholdError> {
+        self.check(&ThresholdValidationConfig::default(), total_weight, |_| {
+            ThresholdError::InvalidThreshold {}
+        })
+    }
+
+    /// Like `validate`, but checks percentages against the bounds in `config`
+    /// rather than the defaults
+    pub fn validate_with(
+        &self,
+        config: &ThresholdValidationConfig,
+        total_weight: u64,
+    ) -> Result<(), ThresholdError> {
+        self.check(config, total_weight, |config| {
+            ThresholdError::ThresholdOutOfRange {
+                min: config.min_threshold,
+                max: config.max_threshold,
+            }
+        })
+    }
+
+    // validates against `config`, with `out_of_range` building the error for a threshold
+    // outside of its bounds
+    fn check(
+        &self,
+        config: &ThresholdValidationConfig,
+        total_weight: u64,
+        out_of_range: OutOfRange,
+    ) -> Result<(), ThresholdError> {
         match self {
             Threshold::AbsoluteCount {
                 weight: weight_needed,
             } => {
-                if *weight_needed == 0 {
+                if *weight_needed == 0 && !config.allow_zero_weight {
                     Err(ThresholdError::ZeroWeight {})
                 } else if *weight_needed > total_weight {
                     Err(ThresholdError::UnreachableWeight {})
@@ -44,17 +96,58 @@ impl Threshold {
             }
             Threshold::AbsolutePercentage {
                 percentage: percentage_needed,
-            } => valid_threshold(percentage_needed),
+            } => valid_threshold(percentage_needed, config, out_of_range),
             Threshold::ThresholdQuorum {
                 threshold,
                 quorum: quroum,
             } => {
-                valid_threshold(threshold)?;
-                valid_quorum(quroum)
+                valid_threshold(threshold, config, out_of_range)?;
+                valid_quorum(quroum, config)
+            }
+            Threshold::Tiered { default, tiers } => {
+                if matches!(**default, Threshold::Tiered { .. }) {
+                    return Err(ThresholdError::NestedTiers {});
+                }
+                default.check(config, total_weight, out_of_range)?;
+                for (i, tier) in tiers.iter().enumerate() {
+                    if matches!(tier.threshold, Threshold::Tiered { .. }) {
+                        return Err(ThresholdError::NestedTiers {});
+                    }
+                    if tiers[..i].iter().any(|t| t.kind == tier.kind) {
+                        return Err(ThresholdError::DuplicateTier(tier.kind.clone()));
+                    }
+                    tier.threshold.check(config, total_weight, out_of_range)?;
+                }
+                Ok(())
+            }
+            Threshold::WithVeto { threshold, veto } => {
+                if matches!(
+                    **threshold,
+                    Threshold::Tiered { .. } | Threshold::WithVeto { .. }
+                ) {
+                    return Err(ThresholdError::InvalidVetoThreshold {});
+                }
+                threshold.check(config, total_weight, out_of_range)?;
+                valid_veto(veto)
             }
         }
     }
 
+    /// Returns the threshold that applies to a proposal of the given kind.
+    /// This is only different from `self` for `Tiered`, where it errors if `kind` is
+    /// not one of the tiers. Proposals without a kind use the default tier.
+    pub fn for_kind(&self, kind: Option<&str>) -> Result<&Threshold, ThresholdError> {
+        match (self, kind) {
+            (Threshold::Tiered { default, .. }, None) => Ok(default),
+            (Threshold::Tiered { tiers, .. }, Some(kind)) => tiers
+                .iter()
+                .find(|tier| tier.kind == kind)
+                .map(|tier| &tier.threshold)
+                .ok_or_else(|| ThresholdError::UnknownTier(kind.to_string())),
+            _ => Ok(self),
+        }
+    }
+
     /// Creates a response from the saved data, just missing the total_weight info
     pub fn to_response(&self, total_weight: u64) -> ThresholdResponse {
         match self.clone() {
@@ -73,30 +166,264 @@ impl Threshold {
                     total_weight,
                 }
             }
+            Threshold::Tiered { default, tiers } => ThresholdResponse::Tiered {
+                default: Box::new(default.to_response(total_weight)),
+                tiers: tiers
+                    .into_iter()
+                    .map(|tier| ThresholdTierResponse {
+                        kind: tier.kind,
+                        threshold: tier.threshold.to_response(total_weight),
+                    })
+                    .collect(),
+                total_weight,
+            },
+            Threshold::WithVeto { threshold, veto } => ThresholdResponse::WithVeto {
+                threshold: Box::new(threshold.to_response(total_weight)),
+                veto,
+                total_weight,
+            },
+        }
+    }
+
+    /// Returns the weight of Yes votes needed to pass, given the weight this threshold
+    /// applies to (usually the total weight, minus abstained votes).
+    ///
+    /// Percentages are applied with exact fixed-point math and always rounded up,
+    /// so e.g. 50% of 15 requires 8. For `AbsoluteCount`, this is the configured weight.
+    pub fn required_weight(&self, total_weight: u64) -> Result<u64, ThresholdError> {
+        match self {
+            Threshold::AbsoluteCount { weight } => Ok(*weight),
+            Threshold::AbsolutePercentage { percentage } => votes_needed(total_weight, *percentage),
+            Threshold::ThresholdQuorum { threshold, .. } => votes_needed(total_weight, *threshold),
+            Threshold::Tiered { default, .. } => default.required_weight(total_weight),
+            Threshold::WithVeto { threshold, .. } => threshold.required_weight(total_weight),
+        }
+    }
+
+    /// Returns the weight that must participate in the vote (including abstain) for it to
+    /// be considered at all, rounded up like `required_weight`.
+    /// This is zero if this threshold does not require a quorum.
+    pub fn required_quorum_weight(&self, total_weight: u64) -> Result<u64, ThresholdError> {
+        match self {
+            Threshold::ThresholdQuorum { quorum, .. } => votes_needed(total_weight, *quorum),
+            Threshold::Tiered { default, .. } => default.required_quorum_weight(total_weight),
+            Threshold::WithVeto { threshold, .. } => threshold.required_quorum_weight(total_weight),
+            _ => Ok(0),
+        }
+    }
+
+    /// Returns the status of a proposal voted on with this threshold, given the current
+    /// tally, the total weight snapshotted at proposal creation, and whether the voting
+    /// period has expired.
+    ///
+    /// While the voting period is open, this implements the early pass rules described in
+    /// `ThresholdResponse`, and rejects early once no sequence of remaining votes can make
+    /// the proposal pass. Once expired, a proposal is either `Passed` or `Rejected`.
+    /// A proposal never passes without any Yes weight.
+    ///
+    /// `Tiered` thresholds use their default tier here, use `for_kind` to select another one.
+    /// Errors if the weights of `votes` overflow when summed up.
+    pub fn status(
+        &self,
+        votes: &Votes,
+        total_weight: u64,
+        expired: bool,
+    ) -> Result<VoteStatus, ThresholdError> {
+        match self {
+            Threshold::Tiered { default, .. } => {
+                return default.status(votes, total_weight, expired);
+            }
+            Threshold::WithVeto { threshold, veto } => {
+                let vetoed =
+                    |veto_weight: u64| veto_weight > 0 && reaches(veto_weight, total_weight, *veto);
+                if vetoed(votes.veto) {
+                    return Ok(VoteStatus::Rejected);
+                }
+                let status = threshold.status(votes, total_weight, expired)?;
+                // we cannot pass early while the remaining weight can still veto
+                let remaining = total_weight.saturating_sub(votes.total()?);
+                if status == VoteStatus::Passed && !expired && vetoed(votes.veto + remaining) {
+                    return Ok(VoteStatus::Open);
+                }
+                return Ok(status);
+            }
+            _ => {}
+        }
+        if self.is_passed(votes, total_weight, expired)? {
+            Ok(VoteStatus::Passed)
+        } else if expired || self.is_rejected(votes, total_weight)? {
+            Ok(VoteStatus::Rejected)
+        } else {
+            Ok(VoteStatus::Open)
+        }
+    }
+
+    /// returns true if the proposal has passed. If not expired, this only returns true
+    /// if no sequence of remaining votes can make it fail.
+    fn is_passed(
+        &self,
+        votes: &Votes,
+        total_weight: u64,
+        expired: bool,
+    ) -> Result<bool, ThresholdError> {
+        if votes.yes == 0 {
+            return Ok(false);
+        }
+        match self {
+            Threshold::AbsoluteCount {
+                weight: weight_needed,
+            } => Ok(votes.yes >= *weight_needed),
+            Threshold::AbsolutePercentage {
+                percentage: percentage_needed,
+            } => {
+                let opinions = total_weight.saturating_sub(votes.abstain);
+                Ok(reaches(votes.yes, opinions, *percentage_needed))
+            }
+            Threshold::ThresholdQuorum { threshold, quorum } => {
+                let cast = votes.total()?;
+                // we always require the quorum
+                if !reaches(cast, total_weight, *quorum) {
+                    return Ok(false);
+                }
+                let opinions = if expired {
+                    // once expired, we only compare against the votes cast (minus abstain)
+                    cast - votes.abstain
+                } else {
+                    // if not expired, we must assume all non-votes will be cast against
+                    total_weight.saturating_sub(votes.abstain)
+                };
+                Ok(reaches(votes.yes, opinions, *threshold))
+            }
+            // these are handled in `status`
+            Threshold::Tiered { .. } | Threshold::WithVeto { .. } => {
+                Ok(self.status(votes, total_weight, expired)? == VoteStatus::Passed)
+            }
         }
     }
+
+    /// returns true if the proposal can no longer pass, even if all remaining
+    /// weight votes Yes
+    fn is_rejected(&self, votes: &Votes, total_weight: u64) -> Result<bool, ThresholdError> {
+        let remaining = total_weight.saturating_sub(votes.total()?);
+        let best_case = Votes {
+            yes: votes.yes + remaining,
+            ..*votes
+        };
+        Ok(!self.is_passed(&best_case, total_weight, true)?)
+    }
 }
 
-/// Asserts that the 0.5 < percent <= 1.0
-fn valid_threshold(percent: &Decimal) -> Result<(), ThresholdError> {
-    if *percent > Decimal::percent(100) || *percent < Decimal::percent(50) {
-        Err(ThresholdError::InvalidThreshold {})
+/// Returns `ceil(weight * percentage)`, computed without any loss of precision, as we
+/// need 8, not 7 votes to reach 50% of 15 total.
+/// This only errors if the result does not fit into a `u64`, which requires a percentage
+/// above 100%.
+fn votes_needed(weight: u64, percentage: Decimal) -> Result<u64, ThresholdError> {
+    let applied = percentage.atomics().full_mul(weight);
+    let denominator = Uint256::from(10u128.pow(percentage.decimal_places()));
+    // Divide by the denominator, rounding up to the nearest integer
+    let needed = (applied + denominator - Uint256::from(1u8)) / denominator;
+    Uint128::try_from(needed)
+        .ok()
+        .and_then(|needed| u64::try_from(needed.u128()).ok())
+        .ok_or_else(|| {
+            StdError::overflow(OverflowError::new(
+                OverflowOperation::Mul,
+                weight,
+                percentage,
+            ))
+            .into()
+        })
+}
+
+/// returns true if `weight` is at least `percentage` of `total_weight`
+fn reaches(weight: u64, total_weight: u64, percentage: Decimal) -> bool {
+    // an overflow means more than `u64::MAX` is needed, which is never reached
+    matches!(votes_needed(total_weight, percentage), Ok(needed) if weight >= needed)
+}
+
+/// Asserts that the min_threshold <= percent <= max_threshold (0.5-1.0 by default)
+fn valid_threshold(
+    percent: &Decimal,
+    config: &ThresholdValidationConfig,
+    out_of_range: OutOfRange,
+) -> Result<(), ThresholdError> {
+    if *percent < config.min_threshold || *percent > config.max_threshold {
+        Err(out_of_range(config))
+    } else if *percent > Decimal::one() {
+        Err(ThresholdError::UnreachableThreshold {})
     } else {
         Ok(())
     }
 }
 
-/// Asserts that the 0.5 < percent <= 1.0
-fn valid_quorum(percent: &Decimal) -> Result<(), ThresholdError> {
-    if percent.is_zero() {
+/// Asserts that the 0 < percent <= 1.0
+fn valid_veto(percent: &Decimal) -> Result<(), ThresholdError> {
+    if percent.is_zero() || *percent > Decimal::one() {
+        Err(ThresholdError::InvalidVetoThreshold {})
+    } else {
+        Ok(())
+    }
+}
+
+/// Asserts that the 0 < percent <= 1.0, and min_quorum <= percent <= max_quorum
+fn valid_quorum(
+    percent: &Decimal,
+    config: &ThresholdValidationConfig,
+) -> Result<(), ThresholdError> {
+    if percent.is_zero() && !config.allow_zero_quorum {
         Err(ThresholdError::ZeroQuorumThreshold {})
     } else if *percent > Decimal::one() {
         Err(ThresholdError::UnreachableQuorumThreshold {})
+    } else if *percent < config.min_quorum || *percent > config.max_quorum {
+        Err(ThresholdError::QuorumOutOfRange {
+            min: config.min_quorum,
+            max: config.max_quorum,
+        })
     } else {
         Ok(())
     }
 }
 
+const DEFAULT_MIN_THRESHOLD: Decimal = Decimal::raw(500_000_000_000_000_000);
+const DEFAULT_MAX_THRESHOLD: Decimal = Decimal::one();
+
+/// Builds the error for a threshold outside of the configured bounds
+type OutOfRange = fn(&ThresholdValidationConfig) -> ThresholdError;
+
+/// The bounds `Threshold::validate_with` checks percentages against.
+///
+/// The default is what `Threshold::validate` uses: a threshold in the 0.5-1.0 range and
+/// a non-zero quorum. Contracts can relax this, e.g. for signalling polls that should
+/// pass with a third of the votes.
+#[cw_serde]
+pub struct ThresholdValidationConfig {
+    /// Minimum `percentage` / `threshold` (inclusive)
+    pub min_threshold: Decimal,
+    /// Maximum `percentage` / `threshold` (inclusive). Thresholds above 100% are never valid.
+    pub max_threshold: Decimal,
+    /// Minimum `quorum` (inclusive)
+    pub min_quorum: Decimal,
+    /// Maximum `quorum` (inclusive). Quorums above 100% are never valid.
+    pub max_quorum: Decimal,
+    /// Allow a zero `quorum`, i.e. no participation requirement
+    pub allow_zero_quorum: bool,
+    /// Allow an `AbsoluteCount` with zero `weight`
+    pub allow_zero_weight: bool,
+}
+
+impl Default for ThresholdValidationConfig {
+    fn default() -> Self {
+        ThresholdValidationConfig {
+            min_threshold: DEFAULT_MIN_THRESHOLD,
+            max_threshold: DEFAULT_MAX_THRESHOLD,
+            min_quorum: Decimal::zero(),
+            max_quorum: Decimal::one(),
+            allow_zero_quorum: false,
+            allow_zero_weight: false,
+        }
+    }
+}
+
 /// This defines the different ways tallies can happen.
 /// Every contract should support a subset of these, ideally all.
 ///
@@ -163,6 +490,109 @@ pub enum ThresholdResponse {
         quorum: Decimal,
         total_weight: u64,
     },
+
+    /// Declares different thresholds depending on the kind of a proposal. Each proposal
+    /// is tallied using the threshold of its kind, or `default` if it has none.
+    ///
+    /// This is useful for groups where proposals have very different impact. For example,
+    /// routine proposals could require 50% (the default), treasury moves 67% and contract
+    /// migrations 80% of the total weight.
+    Tiered {
+        default: Box<ThresholdResponse>,
+        tiers: Vec<ThresholdTierResponse>,
+        total_weight: u64,
+    },
+
+    /// Applies `threshold`, but gives the voters the power to reject a proposal outright:
+    /// as soon as Veto votes reach a `veto` percentage of the total weight, the proposal
+    /// is rejected, no matter how many Yes votes were cast.
+    ///
+    /// A proposal of this type can only pass early once the veto can no longer be reached,
+    /// even if all of the remaining weight was cast as Veto.
+    WithVeto {
+        threshold: Box<ThresholdResponse>,
+        veto: Decimal,
+        total_weight: u64,
+    },
+}
+
+/// The threshold for all proposals of the given `kind` in `ThresholdResponse::Tiered`
+#[cw_serde]
+pub struct ThresholdTierResponse {
+    pub kind: String,
+    pub threshold: ThresholdResponse,
+}
+
+/// A single vote option, as defined in the cw3 spec
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum Vote {
+    /// Marks support for the proposal.
+    Yes,
+    /// Marks opposition to the proposal.
+    No,
+    /// Marks participation but does not count towards the ratio of support / opposed
+    Abstain,
+    /// Veto is generally to be treated as a No vote. Some implementations may allow certain
+    /// voters to be able to Veto, or them to be counted stronger than No in some way.
+    Veto,
+}
+
+/// The weight of all votes cast on a proposal, tallied by vote option
+#[cw_serde]
+#[derive(Copy, Eq, Default)]
+pub struct Votes {
+    pub yes: u64,
+    pub no: u64,
+    pub abstain: u64,
+    pub veto: u64,
+}
+
+impl Votes {
+    /// sum of all votes, erroring on overflow
+    pub fn total(&self) -> Result<u64, ThresholdError> {
+        let total = add_weight(self.yes, self.no)?;
+        let total = add_weight(total, self.abstain)?;
+        add_weight(total, self.veto)
+    }
+
+    /// create it with a yes vote for this much
+    pub fn yes(init_weight: u64) -> Self {
+        Votes {
+            yes: init_weight,
+            ..Votes::default()
+        }
+    }
+
+    /// adds `weight` to the given option, erroring on overflow
+    pub fn add_vote(&mut self, vote: Vote, weight: u64) -> Result<(), ThresholdError> {
+        let tally = match vote {
+            Vote::Yes => &mut self.yes,
+            Vote::No => &mut self.no,
+            Vote::Abstain => &mut self.abstain,
+            Vote::Veto => &mut self.veto,
+        };
+        *tally = add_weight(*tally, weight)?;
+        Ok(())
+    }
+}
+
+// a + b, erroring on overflow
+fn add_weight(a: u64, b: u64) -> Result<u64, ThresholdError> {
+    a.checked_add(b)
+        .ok_or_else(|| StdError::overflow(OverflowError::new(OverflowOperation::Add, a, b)).into())
+}
+
+/// The outcome of a tally, as computed by `Threshold::status`
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum VoteStatus {
+    /// The outcome is not decided yet
+    Open,
+    /// Enough Yes weight was cast for the proposal to pass
+    Passed,
+    /// The proposal can no longer pass
+    Rejected,
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -184,6 +614,27 @@ pub enum ThresholdError {
 
     #[error("Not possible to reach required (passing) weight")]
     UnreachableWeight {},
+
+    #[error("Invalid voting threshold percentage, must be in the {min}-{max} range")]
+    ThresholdOutOfRange { min: Decimal, max: Decimal },
+
+    #[error("Not possible to reach required voting threshold")]
+    UnreachableThreshold {},
+
+    #[error("Invalid quorum percentage, must be in the {min}-{max} range")]
+    QuorumOutOfRange { min: Decimal, max: Decimal },
+
+    #[error("Invalid veto threshold, must be a percentage in the 0.0-1.0 range applied to a non-tiered threshold")]
+    InvalidVetoThreshold {},
+
+    #[error("Tiered thresholds cannot be nested")]
+    NestedTiers {},
+
+    #[error("Duplicate threshold tier '{0}'")]
+    DuplicateTier(String),
+
+    #[error("Unknown threshold tier '{0}'")]
+    UnknownTier(String),
 }
 
 #[cfg(test)]
@@ -193,25 +644,26 @@ mod tests {
     #[test]
     fn validate_quorum_percentage() {
         // TODO: test the error messages
+        let config = ThresholdValidationConfig::default();
 
         // 0 is never a valid percentage
-        let err = valid_quorum(&Decimal::zero()).unwrap_err();
+        let err = valid_quorum(&Decimal::zero(), &config).unwrap_err();
         assert_eq!(
             err.to_string(),
             ThresholdError::ZeroQuorumThreshold {}.to_string()
         );
 
         // 100% is
-        valid_quorum(&Decimal::one()).unwrap();
+        valid_quorum(&Decimal::one(), &config).unwrap();
 
         // 101% is not
-        let err = valid_quorum(&Decimal::percent(101)).unwrap_err();
+        let err = valid_quorum(&Decimal::percent(101), &config).unwrap_err();
         assert_eq!(
             err.to_string(),
             ThresholdError::UnreachableQuorumThreshold {}.to_string()
         );
         // not 100.1%
-        let err = valid_quorum(&Decimal::permille(1001)).unwrap_err();
+        let err = valid_quorum(&Decimal::permille(1001), &config).unwrap_err();
         assert_eq!(
             err.to_string(),
             ThresholdError::UnreachableQuorumThreshold {}.to_string()
@@ -220,11 +672,13 @@ mod tests {
 
     #[test]
     fn validate_threshold_percentage() {
+        let config = ThresholdValidationConfig::default();
         // other values in between 0.5 and 1 are valid
-        valid_threshold(&Decimal::percent(51)).unwrap();
-        valid_threshold(&Decimal::percent(67)).unwrap();
-        valid_threshold(&Decimal::percent(99)).unwrap();
-        let err = valid_threshold(&Decimal::percent(101)).unwrap_err();
+        let out_of_range: OutOfRange = |_| ThresholdError::InvalidThreshold {};
+        valid_threshold(&Decimal::percent(51), &config, out_of_range).unwrap();
+        valid_threshold(&Decimal::percent(67), &config, out_of_range).unwrap();
+        valid_threshold(&Decimal::percent(99), &config, out_of_range).unwrap();
+        let err = valid_threshold(&Decimal::percent(101), &config, out_of_range).unwrap_err();
         assert_eq!(
             err.to_string(),
             ThresholdError::InvalidThreshold {}.to_string()
@@ -295,6 +749,329 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate_with_config() {
+        // a minority signalling poll
+        let config = ThresholdValidationConfig {
+            min_threshold: Decimal::percent(33),
+            max_quorum: Decimal::percent(50),
+            allow_zero_quorum: true,
+            ..ThresholdValidationConfig::default()
+        };
+        let poll = Threshold::AbsolutePercentage {
+            percentage: Decimal::percent(33),
+        };
+        poll.validate_with(&config, 10).unwrap();
+        // the defaults still reject it
+        let err = poll.validate(10).unwrap_err();
+        assert_eq!(err, ThresholdError::InvalidThreshold {});
+
+        let err = Threshold::AbsolutePercentage {
+            percentage: Decimal::percent(32),
+        }
+        .validate_with(&config, 10)
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ThresholdError::ThresholdOutOfRange {
+                min: Decimal::percent(33),
+                max: Decimal::one()
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Invalid voting threshold percentage, must be in the 0.33-1 range"
+        );
+
+        // quorum bounds
+        let quorum = |quorum: u64| Threshold::ThresholdQuorum {
+            threshold: Decimal::percent(51),
+            quorum: Decimal::percent(quorum),
+        };
+        quorum(0).validate_with(&config, 10).unwrap();
+        quorum(50).validate_with(&config, 10).unwrap();
+        let err = quorum(51).validate_with(&config, 10).unwrap_err();
+        assert_eq!(
+            err,
+            ThresholdError::QuorumOutOfRange {
+                min: Decimal::zero(),
+                max: Decimal::percent(50)
+            }
+        );
+        let config = ThresholdValidationConfig {
+            min_quorum: Decimal::percent(10),
+            max_quorum: Decimal::percent(200),
+            ..ThresholdValidationConfig::default()
+        };
+        let err = quorum(5).validate_with(&config, 10).unwrap_err();
+        assert!(matches!(err, ThresholdError::QuorumOutOfRange { .. }));
+        // zero is rejected unless explicitly allowed
+        let err = quorum(0).validate_with(&config, 10).unwrap_err();
+        assert_eq!(err, ThresholdError::ZeroQuorumThreshold {});
+        // more than 100% can never be reached
+        let err = quorum(101).validate_with(&config, 10).unwrap_err();
+        assert_eq!(err, ThresholdError::UnreachableQuorumThreshold {});
+
+        // and neither can a threshold above 100%, even if the bounds allow it
+        let config = ThresholdValidationConfig {
+            max_threshold: Decimal::percent(200),
+            ..ThresholdValidationConfig::default()
+        };
+        Threshold::AbsolutePercentage {
+            percentage: Decimal::one(),
+        }
+        .validate_with(&config, 10)
+        .unwrap();
+        let err = Threshold::ThresholdQuorum {
+            threshold: Decimal::percent(150),
+            quorum: Decimal::percent(20),
+        }
+        .validate_with(&config, 10)
+        .unwrap_err();
+        assert_eq!(err, ThresholdError::UnreachableThreshold {});
+
+        // zero weight
+        let config = ThresholdValidationConfig {
+            allow_zero_weight: true,
+            ..ThresholdValidationConfig::default()
+        };
+        Threshold::AbsoluteCount { weight: 0 }
+            .validate_with(&config, 10)
+            .unwrap();
+        let err = Threshold::AbsoluteCount { weight: 11 }
+            .validate_with(&config, 10)
+            .unwrap_err();
+        assert_eq!(err, ThresholdError::UnreachableWeight {});
+
+        // the config applies to all tiers
+        let config = ThresholdValidationConfig {
+            min_threshold: Decimal::percent(60),
+            ..ThresholdValidationConfig::default()
+        };
+        let err = tiered().validate_with(&config, 10).unwrap_err();
+        assert!(matches!(err, ThresholdError::ThresholdOutOfRange { .. }));
+    }
+
+    fn tiered() -> Threshold {
+        Threshold::Tiered {
+            default: Box::new(Threshold::AbsolutePercentage {
+                percentage: Decimal::percent(50),
+            }),
+            tiers: vec![
+                ThresholdTier {
+                    kind: "treasury".to_string(),
+                    threshold: Threshold::AbsolutePercentage {
+                        percentage: Decimal::percent(67),
+                    },
+                },
+                ThresholdTier {
+                    kind: "migration".to_string(),
+                    threshold: Threshold::WithVeto {
+                        threshold: Box::new(Threshold::AbsolutePercentage {
+                            percentage: Decimal::percent(80),
+                        }),
+                        veto: Decimal::percent(10),
+                    },
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn validate_tiered() {
+        tiered().validate(10).unwrap();
+
+        // every tier is validated
+        let mut threshold = tiered();
+        if let Threshold::Tiered { tiers, .. } = &mut threshold {
+            tiers[0].threshold = Threshold::AbsoluteCount { weight: 11 };
+        }
+        let err = threshold.validate(10).unwrap_err();
+        assert_eq!(err, ThresholdError::UnreachableWeight {});
+
+        // as is the default
+        let threshold = Threshold::Tiered {
+            default: Box::new(Threshold::AbsoluteCount { weight: 0 }),
+            tiers: vec![],
+        };
+        let err = threshold.validate(10).unwrap_err();
+        assert_eq!(err, ThresholdError::ZeroWeight {});
+
+        // kinds must be unique
+        let mut threshold = tiered();
+        if let Threshold::Tiered { tiers, .. } = &mut threshold {
+            tiers[1].kind = "treasury".to_string();
+        }
+        let err = threshold.validate(10).unwrap_err();
+        assert_eq!(err, ThresholdError::DuplicateTier("treasury".to_string()));
+
+        // tiers cannot be nested
+        let threshold = Threshold::Tiered {
+            default: Box::new(tiered()),
+            tiers: vec![],
+        };
+        let err = threshold.validate(10).unwrap_err();
+        assert_eq!(err, ThresholdError::NestedTiers {});
+        let threshold = Threshold::Tiered {
+            default: Box::new(Threshold::AbsoluteCount { weight: 1 }),
+            tiers: vec![ThresholdTier {
+                kind: "nested".to_string(),
+                threshold: tiered(),
+            }],
+        };
+        let err = threshold.validate(10).unwrap_err();
+        assert_eq!(err, ThresholdError::NestedTiers {});
+    }
+
+    #[test]
+    fn validate_veto() {
+        let with_veto = |veto: Decimal| Threshold::WithVeto {
+            threshold: Box::new(Threshold::AbsoluteCount { weight: 3 }),
+            veto,
+        };
+        with_veto(Decimal::percent(1)).validate(5).unwrap();
+        with_veto(Decimal::one()).validate(5).unwrap();
+
+        let err = with_veto(Decimal::zero()).validate(5).unwrap_err();
+        assert_eq!(err, ThresholdError::InvalidVetoThreshold {});
+        let err = with_veto(Decimal::percent(101)).validate(5).unwrap_err();
+        assert_eq!(err, ThresholdError::InvalidVetoThreshold {});
+
+        // the wrapped threshold is validated
+        let err = with_veto(Decimal::percent(10)).validate(2).unwrap_err();
+        assert_eq!(err, ThresholdError::UnreachableWeight {});
+
+        // only simple thresholds can be wrapped
+        let threshold = Threshold::WithVeto {
+            threshold: Box::new(with_veto(Decimal::percent(10))),
+            veto: Decimal::percent(10),
+        };
+        let err = threshold.validate(5).unwrap_err();
+        assert_eq!(err, ThresholdError::InvalidVetoThreshold {});
+        let threshold = Threshold::WithVeto {
+            threshold: Box::new(tiered()),
+            veto: Decimal::percent(10),
+        };
+        let err = threshold.validate(5).unwrap_err();
+        assert_eq!(err, ThresholdError::InvalidVetoThreshold {});
+    }
+
+    #[test]
+    fn tiered_for_kind() {
+        let threshold = tiered();
+        assert_eq!(
+            threshold.for_kind(None).unwrap(),
+            &Threshold::AbsolutePercentage {
+                percentage: Decimal::percent(50)
+            }
+        );
+        assert_eq!(
+            threshold.for_kind(Some("treasury")).unwrap(),
+            &Threshold::AbsolutePercentage {
+                percentage: Decimal::percent(67)
+            }
+        );
+        let err = threshold.for_kind(Some("other")).unwrap_err();
+        assert_eq!(err, ThresholdError::UnknownTier("other".to_string()));
+
+        // non-tiered thresholds apply to all kinds
+        let threshold = Threshold::AbsoluteCount { weight: 3 };
+        assert_eq!(threshold.for_kind(Some("treasury")).unwrap(), &threshold);
+        assert_eq!(threshold.for_kind(None).unwrap(), &threshold);
+
+        // the default tier is used when tallying directly
+        let tally = votes(6, 0, 0, 0);
+        assert_eq!(
+            tiered().status(&tally, 10, false).unwrap(),
+            VoteStatus::Passed
+        );
+        let treasury = tiered().for_kind(Some("treasury")).unwrap().clone();
+        assert_eq!(
+            treasury.status(&tally, 10, false).unwrap(),
+            VoteStatus::Open
+        );
+        assert_eq!(tiered().required_weight(10).unwrap(), 5);
+        assert_eq!(treasury.required_weight(10).unwrap(), 7);
+    }
+
+    #[test]
+    fn veto_status() {
+        let threshold = Threshold::WithVeto {
+            threshold: Box::new(Threshold::AbsoluteCount { weight: 6 }),
+            veto: Decimal::percent(20),
+        };
+        assert_eq!(threshold.required_weight(10).unwrap(), 6);
+
+        // veto rejects regardless of yes votes
+        assert_eq!(
+            threshold.status(&votes(8, 0, 0, 2), 10, false).unwrap(),
+            VoteStatus::Rejected
+        );
+        assert_eq!(
+            threshold.status(&votes(0, 0, 0, 2), 10, false).unwrap(),
+            VoteStatus::Rejected
+        );
+        // cannot pass early while a veto is possible
+        assert_eq!(
+            threshold.status(&votes(7, 0, 0, 1), 10, false).unwrap(),
+            VoteStatus::Open
+        );
+        assert_eq!(
+            threshold.status(&votes(8, 1, 0, 1), 10, false).unwrap(),
+            VoteStatus::Passed
+        );
+        // but passes at expiration without enough vetoes
+        assert_eq!(
+            threshold.status(&votes(7, 0, 0, 1), 10, true).unwrap(),
+            VoteStatus::Passed
+        );
+        // and is still rejected by the wrapped threshold
+        assert_eq!(
+            threshold.status(&votes(1, 4, 0, 1), 10, false).unwrap(),
+            VoteStatus::Rejected
+        );
+    }
+
+    #[test]
+    fn json_is_backwards_compatible() {
+        use cosmwasm_std::{from_slice, to_vec};
+
+        for (threshold, json) in [
+            (
+                Threshold::AbsoluteCount { weight: 3 },
+                r#"{"absolute_count":{"weight":3}}"#,
+            ),
+            (
+                Threshold::AbsolutePercentage {
+                    percentage: Decimal::percent(51),
+                },
+                r#"{"absolute_percentage":{"percentage":"0.51"}}"#,
+            ),
+            (
+                Threshold::ThresholdQuorum {
+                    threshold: Decimal::percent(51),
+                    quorum: Decimal::percent(20),
+                },
+                r#"{"threshold_quorum":{"threshold":"0.51","quorum":"0.2"}}"#,
+            ),
+        ] {
+            assert_eq!(to_vec(&threshold).unwrap(), json.as_bytes());
+            assert_eq!(from_slice::<Threshold>(json.as_bytes()).unwrap(), threshold);
+        }
+
+        let threshold = Threshold::WithVeto {
+            threshold: Box::new(Threshold::AbsoluteCount { weight: 3 }),
+            veto: Decimal::percent(10),
+        };
+        let json = r#"{"with_veto":{"threshold":{"absolute_count":{"weight":3}},"veto":"0.1"}}"#;
+        assert_eq!(to_vec(&threshold).unwrap(), json.as_bytes());
+        assert_eq!(from_slice::<Threshold>(json.as_bytes()).unwrap(), threshold);
+        assert_eq!(
+            from_slice::<Threshold>(&to_vec(&tiered()).unwrap()).unwrap(),
+            tiered()
+        );
+    }
+
     #[test]
     fn threshold_response() {
         let total_weight: u64 = 100;
@@ -333,5 +1110,354 @@ mod tests {
                 total_weight
             }
         );
+
+        let res = tiered().to_response(total_weight);
+        assert_eq!(
+            res,
+            ThresholdResponse::Tiered {
+                default: Box::new(ThresholdResponse::AbsolutePercentage {
+                    percentage: Decimal::percent(50),
+                    total_weight
+                }),
+                tiers: vec![
+                    ThresholdTierResponse {
+                        kind: "treasury".to_string(),
+                        threshold: ThresholdResponse::AbsolutePercentage {
+                            percentage: Decimal::percent(67),
+                            total_weight
+                        },
+                    },
+                    ThresholdTierResponse {
+                        kind: "migration".to_string(),
+                        threshold: ThresholdResponse::WithVeto {
+                            threshold: Box::new(ThresholdResponse::AbsolutePercentage {
+                                percentage: Decimal::percent(80),
+                                total_weight
+                            }),
+                            veto: Decimal::percent(10),
+                            total_weight
+                        },
+                    },
+                ],
+                total_weight
+            }
+        );
+    }
+
+    fn votes(yes: u64, no: u64, abstain: u64, veto: u64) -> Votes {
+        Votes {
+            yes,
+            no,
+            abstain,
+            veto,
+        }
+    }
+
+    #[test]
+    fn votes_tally() {
+        let mut votes = Votes::yes(3);
+        votes.add_vote(Vote::No, 2).unwrap();
+        votes.add_vote(Vote::Abstain, 4).unwrap();
+        votes.add_vote(Vote::Veto, 1).unwrap();
+        votes.add_vote(Vote::Yes, 5).unwrap();
+        assert_eq!(votes, self::votes(8, 2, 4, 1));
+        assert_eq!(votes.total().unwrap(), 15);
+
+        // weights that do not fit into u64 error
+        let err = votes.add_vote(Vote::No, u64::MAX).unwrap_err();
+        assert!(matches!(
+            err,
+            ThresholdError::Std(StdError::Overflow { .. })
+        ));
+        assert_eq!(votes, self::votes(8, 2, 4, 1));
+        let votes = self::votes(u64::MAX, 0, 1, 0);
+        let err = votes.total().unwrap_err();
+        assert!(matches!(
+            err,
+            ThresholdError::Std(StdError::Overflow { .. })
+        ));
+        let threshold = Threshold::ThresholdQuorum {
+            threshold: Decimal::percent(50),
+            quorum: Decimal::percent(10),
+        };
+        threshold.status(&votes, u64::MAX, true).unwrap_err();
+    }
+
+    #[test]
+    fn votes_needed_rounds_up() {
+        assert_eq!(votes_needed(15, Decimal::percent(50)).unwrap(), 8);
+        assert_eq!(votes_needed(14, Decimal::percent(50)).unwrap(), 7);
+        assert_eq!(votes_needed(5, Decimal::percent(51)).unwrap(), 3);
+        assert_eq!(votes_needed(9, Decimal::percent(51)).unwrap(), 5);
+        assert_eq!(votes_needed(0, Decimal::percent(51)).unwrap(), 0);
+        assert_eq!(votes_needed(100, Decimal::one()).unwrap(), 100);
+
+        // the smallest fraction above 50% needs more than half
+        let just_above_half = Decimal::from_atomics(500_000_000_000_000_001u128, 18).unwrap();
+        assert_eq!(votes_needed(2, just_above_half).unwrap(), 2);
+        assert_eq!(votes_needed(10, just_above_half).unwrap(), 6);
+        let two_thirds = Decimal::from_ratio(2u128, 3u128);
+        assert_eq!(votes_needed(3, two_thirds).unwrap(), 2);
+        assert_eq!(votes_needed(300, two_thirds).unwrap(), 200);
+
+        // full u64 range
+        assert_eq!(votes_needed(u64::MAX, Decimal::one()).unwrap(), u64::MAX);
+        assert_eq!(
+            votes_needed(u64::MAX, Decimal::percent(50)).unwrap(),
+            u64::MAX / 2 + 1
+        );
+        let err = votes_needed(u64::MAX, Decimal::percent(101)).unwrap_err();
+        assert!(matches!(
+            err,
+            ThresholdError::Std(StdError::Overflow { .. })
+        ));
+    }
+
+    #[test]
+    fn required_weight() {
+        let threshold = Threshold::AbsoluteCount { weight: 3 };
+        assert_eq!(threshold.required_weight(5).unwrap(), 3);
+        assert_eq!(threshold.required_weight(100).unwrap(), 3);
+        assert_eq!(threshold.required_quorum_weight(100).unwrap(), 0);
+
+        let threshold = Threshold::AbsolutePercentage {
+            percentage: Decimal::percent(51),
+        };
+        assert_eq!(threshold.required_weight(5).unwrap(), 3);
+        assert_eq!(threshold.required_weight(9).unwrap(), 5);
+        assert_eq!(threshold.required_weight(100).unwrap(), 51);
+        assert_eq!(threshold.required_quorum_weight(100).unwrap(), 0);
+
+        let threshold = Threshold::ThresholdQuorum {
+            threshold: Decimal::percent(67),
+            quorum: Decimal::percent(33),
+        };
+        assert_eq!(threshold.required_weight(10).unwrap(), 7);
+        assert_eq!(threshold.required_weight(100).unwrap(), 67);
+        assert_eq!(threshold.required_quorum_weight(10).unwrap(), 4);
+        assert_eq!(threshold.required_quorum_weight(100).unwrap(), 33);
+
+        // unvalidated percentages may overflow
+        let threshold = Threshold::ThresholdQuorum {
+            threshold: Decimal::percent(67),
+            quorum: Decimal::percent(200),
+        };
+        let err = threshold.required_quorum_weight(u64::MAX).unwrap_err();
+        assert!(matches!(
+            err,
+            ThresholdError::Std(StdError::Overflow { .. })
+        ));
+    }
+
+    #[test]
+    fn absolute_count_status() {
+        let threshold = Threshold::AbsoluteCount { weight: 3 };
+
+        // passes early as soon as the weight is reached
+        assert_eq!(
+            threshold.status(&votes(3, 0, 0, 0), 5, false).unwrap(),
+            VoteStatus::Passed
+        );
+        assert_eq!(
+            threshold.status(&votes(2, 1, 1, 0), 5, false).unwrap(),
+            VoteStatus::Open
+        );
+        // rejected early once 3 yes are no longer possible
+        assert_eq!(
+            threshold.status(&votes(1, 2, 0, 1), 5, false).unwrap(),
+            VoteStatus::Rejected
+        );
+        // and rejected once expired without enough yes
+        assert_eq!(
+            threshold.status(&votes(2, 0, 0, 0), 5, true).unwrap(),
+            VoteStatus::Rejected
+        );
+        assert_eq!(
+            threshold.status(&votes(3, 2, 0, 0), 5, true).unwrap(),
+            VoteStatus::Passed
+        );
+    }
+
+    #[test]
+    fn absolute_percentage_status() {
+        // example from the `ThresholdResponse` docs
+        let threshold = Threshold::AbsolutePercentage {
+            percentage: Decimal::percent(51),
+        };
+        assert_eq!(
+            threshold.status(&votes(3, 0, 0, 0), 5, false).unwrap(),
+            VoteStatus::Passed
+        );
+        assert_eq!(
+            threshold.status(&votes(3, 0, 0, 0), 9, false).unwrap(),
+            VoteStatus::Open
+        );
+        assert_eq!(
+            threshold.status(&votes(5, 0, 0, 0), 9, false).unwrap(),
+            VoteStatus::Passed
+        );
+
+        // abstain votes are excluded from the total weight
+        assert_eq!(
+            threshold.status(&votes(3, 0, 4, 0), 9, false).unwrap(),
+            VoteStatus::Passed
+        );
+        assert_eq!(
+            threshold.status(&votes(2, 0, 4, 0), 9, true).unwrap(),
+            VoteStatus::Rejected
+        );
+
+        // no and veto count against it
+        assert_eq!(
+            threshold.status(&votes(1, 3, 0, 2), 9, false).unwrap(),
+            VoteStatus::Rejected
+        );
+
+        // everybody abstaining does not pass
+        assert_eq!(
+            threshold.status(&votes(0, 0, 9, 0), 9, true).unwrap(),
+            VoteStatus::Rejected
+        );
+    }
+
+    #[test]
+    fn threshold_quorum_status() {
+        // example from the `ThresholdResponse` docs: 30% Yes, 10% No, 20% Abstain
+        let tally = votes(30, 10, 20, 0);
+        let status = |threshold: u64, quorum: u64, expired: bool| {
+            Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(threshold),
+                quorum: Decimal::percent(quorum),
+            }
+            .status(&tally, 100, expired)
+            .unwrap()
+        };
+
+        // passes early if quorum <= 60% and threshold <= 37.5%
+        assert_eq!(status(37, 60, false), VoteStatus::Passed);
+        assert_eq!(status(38, 60, false), VoteStatus::Open);
+        assert_eq!(status(37, 61, false), VoteStatus::Open);
+
+        // once expired, passes if quorum <= 60% and threshold <= 75%
+        assert_eq!(status(75, 60, true), VoteStatus::Passed);
+        assert_eq!(status(76, 60, true), VoteStatus::Rejected);
+        assert_eq!(status(75, 61, true), VoteStatus::Rejected);
+
+        // rejected early if the threshold cannot be reached with the remaining weight
+        assert_eq!(status(87, 60, false), VoteStatus::Open);
+        assert_eq!(status(88, 60, false), VoteStatus::Rejected);
+    }
+
+    /// All thresholds we check the properties for, given a total weight
+    fn all_thresholds(total_weight: u64) -> Vec<Threshold> {
+        let mut thresholds: Vec<_> = (1..=total_weight)
+            .map(|weight| Threshold::AbsoluteCount { weight })
+            .collect();
+        for percentage in [50, 51, 60, 66, 67, 75, 99, 100] {
+            thresholds.push(Threshold::AbsolutePercentage {
+                percentage: Decimal::percent(percentage),
+            });
+            for quorum in [1, 20, 33, 50, 51, 100] {
+                thresholds.push(Threshold::ThresholdQuorum {
+                    threshold: Decimal::percent(percentage),
+                    quorum: Decimal::percent(quorum),
+                });
+            }
+        }
+        let vetoable: Vec<_> = thresholds
+            .iter()
+            .flat_map(|threshold| {
+                [1, 34, 50, 100].map(|veto| Threshold::WithVeto {
+                    threshold: Box::new(threshold.clone()),
+                    veto: Decimal::percent(veto),
+                })
+            })
+            .collect();
+        thresholds.extend(vetoable);
+        thresholds
+    }
+
+    /// All possible tallies where at most total_weight has been cast
+    fn all_tallies(total_weight: u64) -> Vec<Votes> {
+        let mut tallies = vec![];
+        for yes in 0..=total_weight {
+            for no in 0..=total_weight - yes {
+                for abstain in 0..=total_weight - yes - no {
+                    for veto in 0..=total_weight - yes - no - abstain {
+                        tallies.push(votes(yes, no, abstain, veto));
+                    }
+                }
+            }
+        }
+        tallies
+    }
+
+    /// All tallies reachable from `votes` by casting (some of) the remaining weight
+    fn completions(votes: &Votes, total_weight: u64) -> Vec<Votes> {
+        let remaining = total_weight - votes.total().unwrap();
+        all_tallies(remaining)
+            .into_iter()
+            .map(|more| {
+                self::votes(
+                    votes.yes + more.yes,
+                    votes.no + more.no,
+                    votes.abstain + more.abstain,
+                    votes.veto + more.veto,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn status_properties() {
+        for total_weight in 1..=6 {
+            for threshold in all_thresholds(total_weight) {
+                threshold.validate(total_weight).unwrap();
+                for tally in all_tallies(total_weight) {
+                    let open = threshold.status(&tally, total_weight, false).unwrap();
+                    let expired = threshold.status(&tally, total_weight, true).unwrap();
+
+                    // an expired proposal is always decided
+                    assert_ne!(expired, VoteStatus::Open);
+                    // an early decision holds at expiration
+                    if open != VoteStatus::Open {
+                        assert_eq!(open, expired, "{:?} {:?}", threshold, tally);
+                    }
+
+                    // once all weight has voted, the result is final
+                    if tally.total().unwrap() == total_weight {
+                        assert_eq!(open, expired, "{:?} {:?}", threshold, tally);
+                    }
+
+                    let outcomes: Vec<_> = completions(&tally, total_weight)
+                        .iter()
+                        .map(|done| threshold.status(done, total_weight, true).unwrap())
+                        .collect();
+                    match open {
+                        // passing early means no remaining votes can make it fail
+                        VoteStatus::Passed => assert!(
+                            outcomes.iter().all(|s| *s == VoteStatus::Passed),
+                            "{:?} {:?}",
+                            threshold,
+                            tally
+                        ),
+                        // rejecting early means no remaining votes can make it pass
+                        VoteStatus::Rejected => assert!(
+                            outcomes.iter().all(|s| *s == VoteStatus::Rejected),
+                            "{:?} {:?}",
+                            threshold,
+                            tally
+                        ),
+                        // an open proposal can still pass
+                        VoteStatus::Open => assert!(
+                            outcomes.contains(&VoteStatus::Passed),
+                            "{:?} {:?}",
+                            threshold,
+                            tally
+                        ),
+                    }
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,725 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    BlockInfo, Decimal, OverflowError, OverflowOperation, StdError, StdResult, Uint128,
+};
+
+use crate::{Curve, Duration, Scheduled};
+
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+/// VestingSchedule describes how an amount unlocks over time.
+/// All points and durations in one schedule must be either height- or time-based,
+/// see `validate`.
+#[cw_serde]
+pub enum VestingSchedule {
+    /// Vests linearly from `start` until `start + duration`.
+    /// Nothing is vested before `start + cliff`, then everything accrued so far unlocks at once.
+    Linear {
+        start: Scheduled,
+        cliff: Option<Duration>,
+        duration: Duration,
+    },
+    /// Vests `total / steps` at `start + interval`, and again after every following interval,
+    /// until everything is vested after `steps` intervals.
+    Stepped {
+        start: Scheduled,
+        interval: Duration,
+        steps: u64,
+    },
+    /// Vests the given fraction of the total at every point, and linearly in between.
+    /// Points must be in strictly increasing order with non-decreasing fractions,
+    /// ending at one. Nothing is vested before the first point.
+    Piecewise { points: Vec<(Scheduled, Decimal)> },
+    /// Vests a fixed `amount` per `interval` starting at `start`, independent of the total,
+    /// until everything is vested.
+    Saturating {
+        start: Scheduled,
+        amount: Uint128,
+        interval: Duration,
+    },
+}
+
+impl VestingSchedule {
+    /// returns an error if the schedule mixes heights and times, or can never vest the total
+    pub fn validate(&self) -> StdResult<()> {
+        match self {
+            VestingSchedule::Linear {
+                start,
+                cliff,
+                duration,
+            } => {
+                let axis = Axis::of(duration);
+                axis.start(start)?;
+                let duration = axis.length(duration)?;
+                if duration == 0 {
+                    return Err(StdError::generic_err("Vesting duration cannot be zero"));
+                }
+                if let Some(cliff) = cliff {
+                    if axis.length(cliff)? > duration {
+                        return Err(StdError::generic_err(
+                            "Vesting cliff cannot be longer than the duration",
+                        ));
+                    }
+                }
+            }
+            VestingSchedule::Stepped {
+                start,
+                interval,
+                steps,
+            } => {
+                let axis = Axis::of(interval);
+                axis.start(start)?;
+                if axis.length(interval)? == 0 || *steps == 0 {
+                    return Err(StdError::generic_err(
+                        "Vesting interval and steps cannot be zero",
+                    ));
+                }
+            }
+            VestingSchedule::Piecewise { points } => {
+                let axis = match points.first() {
+                    Some((first, _)) => Axis::of_scheduled(first)?,
+                    None => return Err(StdError::generic_err("Vesting points cannot be empty")),
+                };
+                let mut previous: Option<(u64, Decimal)> = None;
+                for (at, fraction) in points {
+                    let at = axis.start(at)?;
+                    if let Some((previous_at, previous_fraction)) = previous {
+                        if at <= previous_at || *fraction < previous_fraction {
+                            return Err(StdError::generic_err("Vesting points must be increasing"));
+                        }
+                    }
+                    previous = Some((at, *fraction));
+                }
+                if previous.map(|(_, fraction)| fraction) != Some(Decimal::one()) {
+                    return Err(StdError::generic_err("Vesting points must end at one"));
+                }
+            }
+            VestingSchedule::Saturating {
+                start,
+                amount,
+                interval,
+            } => {
+                let axis = Axis::of(interval);
+                axis.start(start)?;
+                if axis.length(interval)? == 0 || amount.is_zero() {
+                    return Err(StdError::generic_err(
+                        "Vesting interval and amount cannot be zero",
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns how much of `total` is vested at the given block.
+    /// This never decreases over time and never exceeds `total` for a valid schedule,
+    /// so call `validate` once when storing it.
+    /// Returns an error if the schedule cannot be evaluated, e.g. as it mixes heights and times.
+    pub fn vested_amount(&self, total: Uint128, block: &BlockInfo) -> StdResult<Uint128> {
+        match self {
+            VestingSchedule::Linear {
+                start,
+                cliff,
+                duration,
+            } => {
+                let axis = Axis::of(duration);
+                let elapsed = match axis.elapsed(start, block)? {
+                    Some(elapsed) => elapsed,
+                    None => return Ok(Uint128::zero()),
+                };
+                let cliff = cliff.map_or(Ok(0), |cliff| axis.length(&cliff))?;
+                let duration = axis.length(duration)?;
+                if elapsed < cliff {
+                    Ok(Uint128::zero())
+                } else if elapsed >= duration {
+                    Ok(total)
+                } else {
+                    Ok(total.multiply_ratio(elapsed, duration))
+                }
+            }
+            VestingSchedule::Stepped {
+                start,
+                interval,
+                steps,
+            } => {
+                let axis = Axis::of(interval);
+                let interval = axis.length(interval)?;
+                if interval == 0 || *steps == 0 {
+                    return Err(StdError::generic_err(
+                        "Vesting interval and steps cannot be zero",
+                    ));
+                }
+                let elapsed = axis.elapsed(start, block)?.unwrap_or(0);
+                let done = (elapsed / interval).min(*steps as u128);
+                Ok(total.multiply_ratio(done, *steps))
+            }
+            VestingSchedule::Piecewise { points } => {
+                let first = match points.first() {
+                    Some((first, _)) => first,
+                    None => return Err(StdError::generic_err("Vesting points cannot be empty")),
+                };
+                let axis = Axis::of_scheduled(first)?;
+                let position = axis.position(block);
+                if position < axis.start(first)? {
+                    return Ok(Uint128::zero());
+                }
+                let steps = points
+                    .iter()
+                    .map(|(at, fraction)| Ok((axis.start(at)?, fraction_of(total, *fraction)?)))
+                    .collect::<StdResult<_>>()?;
+                Curve::PiecewiseLinear { steps }
+                    .value(position)
+                    .map_err(|err| StdError::generic_err(err.to_string()))
+            }
+            VestingSchedule::Saturating {
+                start,
+                amount,
+                interval,
+            } => {
+                let axis = Axis::of(interval);
+                let interval = axis.length(interval)?;
+                if interval == 0 {
+                    return Err(StdError::generic_err("Vesting interval cannot be zero"));
+                }
+                let elapsed = axis.elapsed(start, block)?.unwrap_or(0);
+                Ok(amount
+                    .checked_multiply_ratio(elapsed, interval)
+                    .map_or(total, |vested| vested.min(total)))
+            }
+        }
+    }
+}
+
+// total * fraction, rounded down
+fn fraction_of(total: Uint128, fraction: Decimal) -> StdResult<Uint128> {
+    total
+        .checked_multiply_ratio(fraction.atomics(), Decimal::one().atomics())
+        .map_err(|_| {
+            StdError::overflow(OverflowError::new(OverflowOperation::Mul, total, fraction))
+        })
+}
+
+/// Whether a schedule is measured in heights or nanoseconds
+#[derive(Clone, Copy)]
+enum Axis {
+    Height,
+    Time,
+}
+
+impl Axis {
+    fn of(duration: &Duration) -> Self {
+        match duration {
+            Duration::Height(_) => Axis::Height,
+            Duration::Time(_) => Axis::Time,
+        }
+    }
+
+    fn of_scheduled(scheduled: &Scheduled) -> StdResult<Self> {
+        match scheduled {
+            Scheduled::AtHeight(_) => Ok(Axis::Height),
+            Scheduled::AtTime(_) => Ok(Axis::Time),
+            _ => Err(StdError::generic_err(
+                "Vesting cannot be scheduled at both height and time",
+            )),
+        }
+    }
+
+    fn start(self, scheduled: &Scheduled) -> StdResult<u64> {
+        match (self, scheduled) {
+            (Axis::Height, Scheduled::AtHeight(height)) => Ok(*height),
+            (Axis::Time, Scheduled::AtTime(time)) => Ok(time.nanos()),
+            _ => Err(StdError::generic_err(
+                "Vesting schedule cannot mix height and time",
+            )),
+        }
+    }
+
+    fn length(self, duration: &Duration) -> StdResult<u128> {
+        match (self, duration) {
+            (Axis::Height, Duration::Height(height)) => Ok(*height as u128),
+            (Axis::Time, Duration::Time(seconds)) => Ok(*seconds as u128 * NANOS_PER_SECOND),
+            _ => Err(StdError::generic_err(
+                "Vesting schedule cannot mix height and time",
+            )),
+        }
+    }
+
+    fn position(self, block: &BlockInfo) -> u64 {
+        match self {
+            Axis::Height => block.height,
+            Axis::Time => block.time.nanos(),
+        }
+    }
+
+    // None if the block is before the start
+    fn elapsed(self, start: &Scheduled, block: &BlockInfo) -> StdResult<Option<u128>> {
+        let start = self.start(start)?;
+        Ok(self.position(block).checked_sub(start).map(u128::from))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{DAY, WEEK};
+    use cosmwasm_std::Timestamp;
+
+    fn block(height: u64, time: u64) -> BlockInfo {
+        BlockInfo {
+            height,
+            time: Timestamp::from_seconds(time),
+            chain_id: "foo".to_string(),
+        }
+    }
+
+    fn at_height(height: u64) -> BlockInfo {
+        block(height, 0)
+    }
+
+    fn at_time(time: u64) -> BlockInfo {
+        block(0, time)
+    }
+
+    fn percent(percent: u64) -> Decimal {
+        Decimal::percent(percent)
+    }
+
+    #[test]
+    fn linear_vesting() {
+        let total = Uint128::new(1000);
+        let schedule = VestingSchedule::Linear {
+            start: Scheduled::AtHeight(100),
+            cliff: None,
+            duration: Duration::Height(100),
+        };
+        schedule.validate().unwrap();
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(0)).unwrap(),
+            Uint128::zero()
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(100)).unwrap(),
+            Uint128::zero()
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(101)).unwrap(),
+            Uint128::new(10)
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(175)).unwrap(),
+            Uint128::new(750)
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(200)).unwrap(),
+            total
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(5000)).unwrap(),
+            total
+        );
+
+        // with a cliff, accrued tokens unlock at once
+        let start = Timestamp::from_seconds(1_000_000);
+        let schedule = VestingSchedule::Linear {
+            start: Scheduled::AtTime(start),
+            cliff: Some(WEEK),
+            duration: Duration::Time(4 * 7 * 86400),
+        };
+        schedule.validate().unwrap();
+        let day = |days: u64| at_time(1_000_000 + days * 86400);
+        assert_eq!(
+            schedule.vested_amount(total, &day(0)).unwrap(),
+            Uint128::zero()
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &day(6)).unwrap(),
+            Uint128::zero()
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &day(7)).unwrap(),
+            Uint128::new(250)
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &day(14)).unwrap(),
+            Uint128::new(500)
+        );
+        assert_eq!(schedule.vested_amount(total, &day(28)).unwrap(), total);
+    }
+
+    #[test]
+    fn stepped_vesting() {
+        let total = Uint128::new(1000);
+        let schedule = VestingSchedule::Stepped {
+            start: Scheduled::AtTime(Timestamp::from_seconds(500)),
+            interval: DAY,
+            steps: 3,
+        };
+        schedule.validate().unwrap();
+        assert_eq!(
+            schedule.vested_amount(total, &at_time(0)).unwrap(),
+            Uint128::zero()
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_time(500)).unwrap(),
+            Uint128::zero()
+        );
+        assert_eq!(
+            schedule
+                .vested_amount(total, &at_time(500 + 86399))
+                .unwrap(),
+            Uint128::zero()
+        );
+        // rounded down
+        assert_eq!(
+            schedule
+                .vested_amount(total, &at_time(500 + 86400))
+                .unwrap(),
+            Uint128::new(333)
+        );
+        assert_eq!(
+            schedule
+                .vested_amount(total, &at_time(500 + 2 * 86400))
+                .unwrap(),
+            Uint128::new(666)
+        );
+        // but the last step vests the remainder
+        assert_eq!(
+            schedule
+                .vested_amount(total, &at_time(500 + 3 * 86400))
+                .unwrap(),
+            total
+        );
+        assert_eq!(
+            schedule
+                .vested_amount(total, &at_time(u64::MAX / 1_000_000_000))
+                .unwrap(),
+            total
+        );
+    }
+
+    #[test]
+    fn piecewise_vesting() {
+        let total = Uint128::new(1000);
+        // 10% at height 100, then up to 50% at 200, then flat until 300 and the rest until 400
+        let schedule = VestingSchedule::Piecewise {
+            points: vec![
+                (Scheduled::AtHeight(100), percent(10)),
+                (Scheduled::AtHeight(200), percent(50)),
+                (Scheduled::AtHeight(300), percent(50)),
+                (Scheduled::AtHeight(400), percent(100)),
+            ],
+        };
+        schedule.validate().unwrap();
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(99)).unwrap(),
+            Uint128::zero()
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(100)).unwrap(),
+            Uint128::new(100)
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(150)).unwrap(),
+            Uint128::new(300)
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(200)).unwrap(),
+            Uint128::new(500)
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(250)).unwrap(),
+            Uint128::new(500)
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(399)).unwrap(),
+            Uint128::new(995)
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(400)).unwrap(),
+            total
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(401)).unwrap(),
+            total
+        );
+    }
+
+    #[test]
+    fn saturating_vesting() {
+        let total = Uint128::new(1000);
+        let schedule = VestingSchedule::Saturating {
+            start: Scheduled::AtHeight(10),
+            amount: Uint128::new(30),
+            interval: Duration::Height(4),
+        };
+        schedule.validate().unwrap();
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(10)).unwrap(),
+            Uint128::zero()
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(12)).unwrap(),
+            Uint128::new(15)
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(14)).unwrap(),
+            Uint128::new(30)
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(110)).unwrap(),
+            Uint128::new(750)
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(200)).unwrap(),
+            total
+        );
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(u64::MAX)).unwrap(),
+            total
+        );
+
+        // no overflow on large amounts
+        let schedule = VestingSchedule::Saturating {
+            start: Scheduled::AtHeight(0),
+            amount: Uint128::MAX,
+            interval: Duration::Height(1),
+        };
+        assert_eq!(
+            schedule.vested_amount(total, &at_height(u64::MAX)).unwrap(),
+            total
+        );
+    }
+
+    #[test]
+    fn validate_schedules() {
+        let time = Scheduled::AtTime(Timestamp::from_seconds(100));
+        let invalid = vec![
+            // mixed kinds
+            VestingSchedule::Linear {
+                start: time,
+                cliff: None,
+                duration: Duration::Height(100),
+            },
+            VestingSchedule::Linear {
+                start: time,
+                cliff: Some(Duration::Height(1)),
+                duration: DAY,
+            },
+            VestingSchedule::Linear {
+                start: Scheduled::AtHeightOrTime {
+                    height: 1,
+                    time: Timestamp::from_seconds(1),
+                },
+                cliff: None,
+                duration: DAY,
+            },
+            // zero
+            VestingSchedule::Linear {
+                start: time,
+                cliff: None,
+                duration: Duration::Time(0),
+            },
+            // cliff after the end
+            VestingSchedule::Linear {
+                start: time,
+                cliff: Some(WEEK),
+                duration: DAY,
+            },
+            VestingSchedule::Stepped {
+                start: time,
+                interval: DAY,
+                steps: 0,
+            },
+            VestingSchedule::Stepped {
+                start: time,
+                interval: Duration::Time(0),
+                steps: 4,
+            },
+            VestingSchedule::Stepped {
+                start: Scheduled::AtHeight(1),
+                interval: DAY,
+                steps: 4,
+            },
+            VestingSchedule::Piecewise { points: vec![] },
+            // not ending at one
+            VestingSchedule::Piecewise {
+                points: vec![(time, percent(50))],
+            },
+            // decreasing
+            VestingSchedule::Piecewise {
+                points: vec![
+                    (Scheduled::AtHeight(1), percent(50)),
+                    (Scheduled::AtHeight(2), percent(40)),
+                    (Scheduled::AtHeight(3), percent(100)),
+                ],
+            },
+            // not strictly increasing
+            VestingSchedule::Piecewise {
+                points: vec![
+                    (Scheduled::AtHeight(1), percent(50)),
+                    (Scheduled::AtHeight(1), percent(100)),
+                ],
+            },
+            // above one
+            VestingSchedule::Piecewise {
+                points: vec![
+                    (Scheduled::AtHeight(1), percent(150)),
+                    (Scheduled::AtHeight(2), percent(100)),
+                ],
+            },
+            VestingSchedule::Piecewise {
+                points: vec![(Scheduled::AtHeight(1), percent(50)), (time, percent(100))],
+            },
+            VestingSchedule::Saturating {
+                start: time,
+                amount: Uint128::zero(),
+                interval: DAY,
+            },
+            VestingSchedule::Saturating {
+                start: time,
+                amount: Uint128::new(1),
+                interval: Duration::Height(1),
+            },
+        ];
+        for schedule in invalid {
+            schedule.validate().unwrap_err();
+        }
+    }
+
+    #[test]
+    fn unusable_schedules_return_errors() {
+        let time = Scheduled::AtTime(Timestamp::from_seconds(100));
+        let unusable = vec![
+            VestingSchedule::Linear {
+                start: time,
+                cliff: None,
+                duration: Duration::Height(100),
+            },
+            VestingSchedule::Stepped {
+                start: time,
+                interval: DAY,
+                steps: 0,
+            },
+            VestingSchedule::Stepped {
+                start: time,
+                interval: Duration::Time(0),
+                steps: 4,
+            },
+            VestingSchedule::Piecewise { points: vec![] },
+            VestingSchedule::Piecewise {
+                points: vec![
+                    (Scheduled::AtHeight(2), percent(50)),
+                    (Scheduled::AtHeight(1), percent(100)),
+                ],
+            },
+            VestingSchedule::Piecewise {
+                points: vec![(Scheduled::AtHeight(1), percent(50)), (time, percent(100))],
+            },
+            VestingSchedule::Saturating {
+                start: time,
+                amount: Uint128::new(1),
+                interval: Duration::Time(0),
+            },
+        ];
+        for schedule in unusable {
+            schedule.validate().unwrap_err();
+            // rather than vesting nothing (or everything)
+            schedule
+                .vested_amount(
+                    Uint128::new(1000),
+                    &block(u64::MAX, u64::MAX / 1_000_000_000),
+                )
+                .unwrap_err();
+        }
+
+        // fractions above one overflow instead of panicking
+        let schedule = VestingSchedule::Piecewise {
+            points: vec![(Scheduled::AtHeight(1), percent(200))],
+        };
+        schedule
+            .vested_amount(Uint128::MAX, &at_height(1))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn vesting_invariants() {
+        let schedules = vec![
+            VestingSchedule::Linear {
+                start: Scheduled::AtHeight(7),
+                cliff: Some(Duration::Height(13)),
+                duration: Duration::Height(71),
+            },
+            VestingSchedule::Linear {
+                start: Scheduled::AtHeight(0),
+                cliff: Some(Duration::Height(50)),
+                duration: Duration::Height(50),
+            },
+            VestingSchedule::Stepped {
+                start: Scheduled::AtHeight(3),
+                interval: Duration::Height(9),
+                steps: 7,
+            },
+            VestingSchedule::Piecewise {
+                points: vec![
+                    (Scheduled::AtHeight(5), percent(0)),
+                    (Scheduled::AtHeight(6), percent(33)),
+                    (Scheduled::AtHeight(40), percent(34)),
+                    (Scheduled::AtHeight(41), percent(90)),
+                    (Scheduled::AtHeight(97), percent(100)),
+                ],
+            },
+            VestingSchedule::Saturating {
+                start: Scheduled::AtHeight(2),
+                amount: Uint128::new(17),
+                interval: Duration::Height(3),
+            },
+        ];
+        let totals = [0u128, 1, 7, 100, 12345, u128::MAX / 3, u128::MAX];
+        for schedule in schedules {
+            schedule.validate().unwrap();
+            for total in totals {
+                let total = Uint128::new(total);
+                let mut previous = Uint128::zero();
+                for height in 0..150 {
+                    let vested = schedule.vested_amount(total, &at_height(height)).unwrap();
+                    assert!(vested >= previous, "{:?} decreased at {}", schedule, height);
+                    assert!(
+                        vested <= total,
+                        "{:?} exceeded total at {}",
+                        schedule,
+                        height
+                    );
+                    previous = vested;
+                }
+                // at some point, everything is vested
+                // (a saturating rate of 17 per 3 blocks cannot vest the huge totals in u64 heights)
+                if matches!(schedule, VestingSchedule::Saturating { .. }) && total.u128() > 12345 {
+                    continue;
+                }
+                assert_eq!(
+                    schedule.vested_amount(total, &at_height(u64::MAX)).unwrap(),
+                    total,
+                    "{:?}",
+                    schedule
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn json_format() {
+        let schedule = VestingSchedule::Piecewise {
+            points: vec![
+                (Scheduled::AtHeight(5), percent(10)),
+                (Scheduled::AtHeight(6), percent(100)),
+            ],
+        };
+        let json = cosmwasm_std::to_vec(&schedule).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&json),
+            r#"{"piecewise":{"points":[[{"at_height":5},"0.1"],[{"at_height":6},"1"]]}}"#
+        );
+        let parsed: VestingSchedule = cosmwasm_std::from_slice(&json).unwrap();
+        assert_eq!(parsed, schedule);
+    }
+}